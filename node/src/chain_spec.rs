@@ -1,6 +1,6 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	pallet_rng_dao::CycleParameters, AccountId, AuraConfig, BalancesConfig, GenesisConfig,
+	GrandpaConfig, RngDaoConfig, Signature, SudoConfig, SystemConfig, MINUTES, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -59,6 +59,8 @@ pub fn development_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 				],
+				// Bot operators
+				vec![get_account_id_from_seed::<sr25519::Public>("Bob")],
 				true,
 			)
 		},
@@ -107,6 +109,11 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
+				// Bot operators
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+					get_account_id_from_seed::<sr25519::Public>("Charlie"),
+				],
 				true,
 			)
 		},
@@ -130,6 +137,7 @@ fn testnet_genesis(
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
+	bots: Vec<AccountId>,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -149,8 +157,25 @@ fn testnet_genesis(
 		},
 		sudo: SudoConfig {
 			// Assign network admin rights.
-			key: Some(root_key),
+			key: Some(root_key.clone()),
 		},
 		transaction_payment: Default::default(),
+		rng_dao: RngDaoConfig {
+			cycle_count: 0,
+			// A demo cycle of the root account, with phases long enough to take part by hand.
+			cycles: vec![(
+				root_key,
+				1_000_000,
+				Some(CycleParameters {
+					delay_before_bots: 10 * MINUTES,
+					delay_before_second_phase: 10 * MINUTES,
+					second_phase_duration: 10 * MINUTES,
+					max_generators: 10,
+					deposit: 1_000_000,
+					min_revealers: 2,
+				}),
+			)],
+			bots,
+		},
 	}
 }
@@ -50,4 +50,8 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Take part in rng-dao cycles through a running node.
+	#[command(subcommand)]
+	Rng(crate::rng::RngCmd),
 }
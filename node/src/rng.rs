@@ -0,0 +1,561 @@
+//! The `rng` sub-command, for taking part in cycles of the rng-dao pallet from the command line.
+//!
+//! Secrets are generated locally and kept in a vault file until they are revealed. Extrinsics are
+//! signed with the key given by `--suri` against the state of the node at `--url`, and either
+//! submitted to that node or, with `--output`, written to a file as hex for submission elsewhere.
+
+use jsonrpsee::{
+	core::client::ClientT,
+	http_client::{HttpClient, HttpClientBuilder},
+	rpc_params,
+};
+use node_template_runtime as runtime;
+use pallet_rng_dao_rpc::{CycleDetails, GeneratorDetails};
+use runtime::{
+	pallet_rng_dao::{
+		commitment::{commitment, Salt, Secret, MAX_SECRET_LEN},
+		Call as RngDaoCall, CycleParameters,
+	},
+	AccountId, Balance, BlockNumber, Hash, Header,
+};
+use sc_cli::Result;
+use serde::{Deserialize, Serialize};
+use sp_core::{hexdisplay::HexDisplay, sr25519, Bytes, Encode, Pair};
+use sp_runtime::{traits::Header as HeaderT, SaturatedConversion};
+
+use std::{
+	fs,
+	io::Write,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+/// Id of a cycle in the runtime.
+type CycleId = u128;
+
+/// Blocks `rng reveal` waits for its reveal to be included before giving up.
+const REVEAL_WAIT_BLOCKS: u64 = 10;
+
+/// Sub-commands for taking part in rng-dao cycles.
+#[derive(Debug, clap::Subcommand)]
+pub enum RngCmd {
+	/// Create a new cycle with a bounty.
+	CreateCycle(CreateCycleCmd),
+
+	/// Commit to a freshly generated secret, which is kept in the vault.
+	Commit(CommitCmd),
+
+	/// Reveal the secret committed to in a cycle.
+	Reveal(RevealCmd),
+
+	/// Show a cycle and its generators.
+	Status(StatusCmd),
+}
+
+impl RngCmd {
+	/// Run the sub-command.
+	pub fn run(&self) -> Result<()> {
+		let runtime = tokio::runtime::Builder::new_current_thread()
+			.enable_all()
+			.build()
+			.map_err(|e| format!("starting tokio runtime: {}", e))?;
+		match self {
+			RngCmd::CreateCycle(cmd) => runtime.block_on(cmd.run()),
+			RngCmd::Commit(cmd) => runtime.block_on(cmd.run()),
+			RngCmd::Reveal(cmd) => runtime.block_on(cmd.run()),
+			RngCmd::Status(cmd) => runtime.block_on(cmd.run()),
+		}
+	}
+}
+
+/// Node to talk to.
+#[derive(Debug, Clone, clap::Args)]
+pub struct RpcParams {
+	/// HTTP RPC endpoint of the node.
+	#[arg(long, value_name = "URL", default_value = "http://127.0.0.1:9933")]
+	pub url: String,
+}
+
+/// Where secrets are kept between committing and revealing.
+#[derive(Debug, Clone, clap::Args)]
+pub struct VaultParams {
+	/// Vault file holding the generated secrets. Created if it does not exist.
+	#[arg(long, value_name = "PATH", default_value = "rng-vault.json")]
+	pub vault: PathBuf,
+}
+
+/// How to sign an extrinsic and what to do with it.
+#[derive(Debug, Clone, clap::Args)]
+pub struct SignerParams {
+	/// Secret URI of the sr25519 key signing the extrinsic, e.g. `//Alice`.
+	#[arg(long, value_name = "SURI")]
+	pub suri: String,
+
+	/// Write the signed extrinsic as hex to this file instead of submitting it.
+	#[arg(long, value_name = "PATH")]
+	pub output: Option<PathBuf>,
+}
+
+/// The `rng create-cycle` command.
+#[derive(Debug, Clone, clap::Args)]
+pub struct CreateCycleCmd {
+	/// Bounty paid out to the generators of the cycle.
+	#[arg(long)]
+	pub bounty: Balance,
+
+	/// Asset to pay the bounty in instead of the native token.
+	#[arg(long)]
+	pub asset: Option<u32>,
+
+	/// Blocks after the start of the cycle before bots can commit. Set it along with all other
+	/// cycle parameters to override the runtime's defaults.
+	#[arg(long)]
+	pub delay_before_bots: Option<BlockNumber>,
+
+	/// Blocks after bots can commit before secrets can be revealed.
+	#[arg(long)]
+	pub delay_before_second_phase: Option<BlockNumber>,
+
+	/// Blocks in which secrets can be revealed.
+	#[arg(long)]
+	pub second_phase_duration: Option<BlockNumber>,
+
+	/// Maximum number of generators of the cycle.
+	#[arg(long)]
+	pub max_generators: Option<u8>,
+
+	/// Deposit of every generator of the cycle.
+	#[arg(long)]
+	pub deposit: Option<Balance>,
+
+	/// Revealed secrets needed for the cycle to complete.
+	#[arg(long)]
+	pub min_revealers: Option<u8>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub rpc: RpcParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub signer: SignerParams,
+}
+
+impl CreateCycleCmd {
+	async fn run(&self) -> Result<()> {
+		let call = RngDaoCall::create_new_rng_cycle {
+			bounty: self.bounty,
+			params: self.params()?,
+			asset: self.asset,
+		};
+		let client = rpc_client(&self.rpc)?;
+		sign_and_dispatch(&client, &self.signer, call.into()).await
+	}
+
+	fn params(&self) -> Result<Option<CycleParameters<Balance, BlockNumber>>> {
+		match (
+			self.delay_before_bots,
+			self.delay_before_second_phase,
+			self.second_phase_duration,
+			self.max_generators,
+			self.deposit,
+			self.min_revealers,
+		) {
+			(None, None, None, None, None, None) => Ok(None),
+			(
+				Some(delay_before_bots),
+				Some(delay_before_second_phase),
+				Some(second_phase_duration),
+				Some(max_generators),
+				Some(deposit),
+				Some(min_revealers),
+			) => Ok(Some(CycleParameters {
+				delay_before_bots,
+				delay_before_second_phase,
+				second_phase_duration,
+				max_generators,
+				deposit,
+				min_revealers,
+			})),
+			_ => Err("either all or none of the cycle parameters must be given".into()),
+		}
+	}
+}
+
+/// The `rng commit` command.
+#[derive(Debug, Clone, clap::Args)]
+pub struct CommitCmd {
+	/// Cycle to commit to.
+	#[arg(long)]
+	pub cycle_id: CycleId,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub rpc: RpcParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub vault: VaultParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub signer: SignerParams,
+}
+
+impl CommitCmd {
+	async fn run(&self) -> Result<()> {
+		let account: AccountId = signer_pair(&self.signer)?.public().into();
+		let client = rpc_client(&self.rpc)?;
+		let mut vault = Vault::load(&self.vault.vault)?;
+		let (secret, salt) = match vault.find(self.cycle_id, &account) {
+			// a commitment which did not make it onchain is sent again with the same secret
+			Some(entry) => {
+				let generators: Vec<GeneratorDetails<AccountId>> =
+					request(&client, "rngDao_generators", rpc_params![self.cycle_id]).await?;
+				if generators.iter().any(|generator| generator.account == account) {
+					return Err(
+						format!("{} already committed to cycle {}", account, self.cycle_id).into()
+					)
+				}
+				(entry.secret.clone(), entry.salt.0)
+			},
+			None => {
+				let secret: [u8; MAX_SECRET_LEN as usize] = rand::random();
+				let salt: Salt = rand::random();
+				// keep the secret before the commitment can make it onchain, so that it can't get
+				// lost
+				vault.secrets.push(VaultEntry {
+					cycle_id: self.cycle_id,
+					account: account.clone(),
+					secret: secret.to_vec(),
+					salt: Hash::from(salt),
+				});
+				vault.store(&self.vault.vault)?;
+				(secret.to_vec(), salt)
+			},
+		};
+
+		let hash = commitment(&self.cycle_id, &account, &salt, &secret);
+		let call = RngDaoCall::send_hash { cycle_id: self.cycle_id, hash };
+		sign_and_dispatch(&client, &self.signer, call.into()).await
+	}
+}
+
+/// The `rng reveal` command.
+#[derive(Debug, Clone, clap::Args)]
+pub struct RevealCmd {
+	/// Cycle to reveal the secret in.
+	#[arg(long)]
+	pub cycle_id: CycleId,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub rpc: RpcParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub vault: VaultParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub signer: SignerParams,
+}
+
+impl RevealCmd {
+	async fn run(&self) -> Result<()> {
+		let account: AccountId = signer_pair(&self.signer)?.public().into();
+		let client = rpc_client(&self.rpc)?;
+		let mut vault = Vault::load(&self.vault.vault)?;
+		if revealed(&client, self.cycle_id, &account).await? {
+			// revealed by an earlier run or with `--output`, the secret is not needed anymore
+			return self.forget(&mut vault, &account)
+		}
+		let entry = vault.find(self.cycle_id, &account).ok_or_else(|| {
+			format!("{} has no secret for cycle {} in the vault", account, self.cycle_id)
+		})?;
+		let secret = Secret::try_from(entry.secret.clone())
+			.map_err(|_| format!("secret in the vault is longer than {} bytes", MAX_SECRET_LEN))?;
+
+		let call =
+			RngDaoCall::reveal_secret { cycle_id: self.cycle_id, secret, salt: entry.salt.0 };
+		sign_and_dispatch(&client, &self.signer, call.into()).await?;
+		if self.signer.output.is_some() {
+			return Ok(())
+		}
+		for _ in 0..REVEAL_WAIT_BLOCKS {
+			tokio::time::sleep(Duration::from_millis(runtime::MILLISECS_PER_BLOCK)).await;
+			if revealed(&client, self.cycle_id, &account).await? {
+				return self.forget(&mut vault, &account)
+			}
+		}
+		println!("reveal is not onchain yet, run `rng reveal` again to check it");
+		Ok(())
+	}
+
+	/// Remove the revealed secret of `account` from the vault.
+	fn forget(&self, vault: &mut Vault, account: &AccountId) -> Result<()> {
+		if vault.remove(self.cycle_id, account) {
+			vault.store(&self.vault.vault)?;
+		}
+		println!("{} revealed its secret in cycle {}", account, self.cycle_id);
+		Ok(())
+	}
+}
+
+/// The `rng status` command.
+#[derive(Debug, Clone, clap::Args)]
+pub struct StatusCmd {
+	/// Cycle to show.
+	#[arg(long)]
+	pub cycle_id: CycleId,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub rpc: RpcParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub vault: VaultParams,
+}
+
+impl StatusCmd {
+	async fn run(&self) -> Result<()> {
+		let client = rpc_client(&self.rpc)?;
+		let details: Option<CycleDetails<AccountId, Balance, BlockNumber>> =
+			request(&client, "rngDao_cycle", rpc_params![self.cycle_id]).await?;
+		let details = details.ok_or_else(|| format!("cycle {} does not exist", self.cycle_id))?;
+		let generators: Vec<GeneratorDetails<AccountId>> =
+			request(&client, "rngDao_generators", rpc_params![self.cycle_id]).await?;
+		let vault = Vault::load(&self.vault.vault)?;
+
+		let cycle = details.cycle;
+		println!("cycle {}: {:?}", self.cycle_id, details.phase);
+		println!("creator: {}", cycle.creator);
+		println!("bounty: {}", cycle.bounty);
+		println!("started: {}", cycle.started);
+		println!("reveals from: {}", details.second_phase_start);
+		println!("deadline: {}", details.deadline);
+		println!("generators: {}/{}", cycle.generators_count, cycle.params.max_generators);
+		println!("revealed: {}/{}", cycle.revealed_count, cycle.params.min_revealers);
+		if details.phase.is_finalised() {
+			println!("random number: {:?}", cycle.random_number);
+		}
+		for generator in generators {
+			println!(
+				"  {} {}{}",
+				generator.account,
+				if generator.revealed { "revealed" } else { "committed" },
+				if vault.find(self.cycle_id, &generator.account).is_some() {
+					", secret in vault"
+				} else {
+					""
+				},
+			);
+		}
+		Ok(())
+	}
+}
+
+/// Secret committed to from `account` in cycle `cycle_id`.
+#[derive(Serialize, Deserialize)]
+struct VaultEntry {
+	cycle_id: CycleId,
+	account: AccountId,
+	#[serde(with = "sp_core::bytes")]
+	secret: Vec<u8>,
+	salt: Hash,
+}
+
+/// Contents of the vault file.
+#[derive(Default, Serialize, Deserialize)]
+struct Vault {
+	secrets: Vec<VaultEntry>,
+}
+
+impl Vault {
+	/// Vault stored at `path`, empty if there is no such file.
+	fn load(path: &Path) -> Result<Self> {
+		if !path.exists() {
+			return Ok(Self::default())
+		}
+		let file =
+			fs::read(path).map_err(|e| format!("reading vault {}: {}", path.display(), e))?;
+		serde_json::from_slice(&file)
+			.map_err(|e| format!("parsing vault {}: {}", path.display(), e).into())
+	}
+
+	/// Store the vault at `path`, readable by the current user only.
+	fn store(&self, path: &Path) -> Result<()> {
+		let json = serde_json::to_vec_pretty(self).map_err(|e| format!("encoding vault: {}", e))?;
+		let mut options = fs::OpenOptions::new();
+		options.write(true).create(true).truncate(true);
+		#[cfg(unix)]
+		{
+			use std::os::unix::fs::OpenOptionsExt;
+			options.mode(0o600);
+		}
+		options
+			.open(path)
+			.and_then(|mut file| file.write_all(&json))
+			.map_err(|e| format!("writing vault {}: {}", path.display(), e).into())
+	}
+
+	fn find(&self, cycle_id: CycleId, account: &AccountId) -> Option<&VaultEntry> {
+		self.secrets
+			.iter()
+			.find(|entry| entry.cycle_id == cycle_id && entry.account == *account)
+	}
+
+	/// Remove the secret of `account` in cycle `cycle_id`, returns whether there was one.
+	fn remove(&mut self, cycle_id: CycleId, account: &AccountId) -> bool {
+		let len = self.secrets.len();
+		self.secrets.retain(|entry| entry.cycle_id != cycle_id || entry.account != *account);
+		self.secrets.len() != len
+	}
+}
+
+/// Versions of the runtime of the node, as returned by `state_getRuntimeVersion`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RuntimeVersion {
+	spec_version: u32,
+	transaction_version: u32,
+}
+
+/// What a transaction is signed against: the chain, the version of its runtime and the block the
+/// transaction is valid from.
+pub struct SigningContext {
+	/// Hash of the genesis block of the chain.
+	pub genesis_hash: Hash,
+	/// Hash of the block the transaction is valid from.
+	pub best_hash: Hash,
+	/// Number of the block the transaction is valid from.
+	pub best_block: BlockNumber,
+	/// `spec_version` of the runtime of the chain.
+	pub spec_version: u32,
+	/// `transaction_version` of the runtime of the chain.
+	pub transaction_version: u32,
+}
+
+fn signer_pair(signer: &SignerParams) -> Result<sr25519::Pair> {
+	sr25519::Pair::from_string(&signer.suri, None)
+		.map_err(|e| format!("invalid --suri: {:?}", e).into())
+}
+
+fn rpc_client(rpc: &RpcParams) -> Result<HttpClient> {
+	HttpClientBuilder::default()
+		.build(&rpc.url)
+		.map_err(|e| format!("connecting to {}: {}", rpc.url, e).into())
+}
+
+/// Whether `account` revealed its secret in cycle `cycle_id`.
+async fn revealed(client: &HttpClient, cycle_id: CycleId, account: &AccountId) -> Result<bool> {
+	let generators: Vec<GeneratorDetails<AccountId>> =
+		request(client, "rngDao_generators", rpc_params![cycle_id]).await?;
+	Ok(generators.iter().any(|generator| generator.account == *account && generator.revealed))
+}
+
+async fn request<R: serde::de::DeserializeOwned>(
+	client: &HttpClient,
+	method: &str,
+	params: jsonrpsee::core::params::ArrayParams,
+) -> Result<R> {
+	client
+		.request(method, params)
+		.await
+		.map_err(|e| format!("{}: {}", method, e).into())
+}
+
+/// Sign `call` with the `--suri` key against the best block and the runtime of the node, and submit
+/// it or write it to `--output`.
+async fn sign_and_dispatch(
+	client: &HttpClient,
+	signer: &SignerParams,
+	call: runtime::RuntimeCall,
+) -> Result<()> {
+	let sender = signer_pair(signer)?;
+	let account: AccountId = sender.public().into();
+
+	let genesis_hash: Option<Hash> = request(client, "chain_getBlockHash", rpc_params![0]).await?;
+	let genesis_hash = genesis_hash.ok_or("node has no genesis block")?;
+	let best: Option<Header> = request(client, "chain_getHeader", rpc_params![]).await?;
+	let best = best.ok_or("node has no best block")?;
+	let nonce: runtime::Index =
+		request(client, "system_accountNextIndex", rpc_params![account]).await?;
+	// the node's runtime may have been upgraded since this binary was built
+	let version: RuntimeVersion =
+		request(client, "state_getRuntimeVersion", rpc_params![best.hash()]).await?;
+
+	let context = SigningContext {
+		genesis_hash,
+		best_hash: best.hash(),
+		best_block: *best.number(),
+		spec_version: version.spec_version,
+		transaction_version: version.transaction_version,
+	};
+	let extrinsic = create_extrinsic(sender, call, nonce, &context);
+	let encoded = extrinsic.encode();
+
+	match &signer.output {
+		Some(path) => {
+			fs::write(path, format!("0x{}\n", HexDisplay::from(&encoded)))
+				.map_err(|e| format!("writing {}: {}", path.display(), e))?;
+			println!("extrinsic written to {}", path.display());
+		},
+		None => {
+			let hash: Hash =
+				request(client, "author_submitExtrinsic", rpc_params![Bytes(encoded)]).await?;
+			println!("extrinsic submitted: {:?}", hash);
+		},
+	}
+	Ok(())
+}
+
+/// Create a transaction using the given `call`, signed against `context`.
+pub fn create_extrinsic(
+	sender: sr25519::Pair,
+	call: runtime::RuntimeCall,
+	nonce: u32,
+	context: &SigningContext,
+) -> runtime::UncheckedExtrinsic {
+	let period = runtime::BlockHashCount::get()
+		.checked_next_power_of_two()
+		.map(|c| c / 2)
+		.unwrap_or(2) as u64;
+	let extra: runtime::SignedExtra = (
+		frame_system::CheckNonZeroSender::<runtime::Runtime>::new(),
+		frame_system::CheckSpecVersion::<runtime::Runtime>::new(),
+		frame_system::CheckTxVersion::<runtime::Runtime>::new(),
+		frame_system::CheckGenesis::<runtime::Runtime>::new(),
+		frame_system::CheckEra::<runtime::Runtime>::from(sp_runtime::generic::Era::mortal(
+			period,
+			context.best_block.saturated_into(),
+		)),
+		frame_system::CheckNonce::<runtime::Runtime>::from(nonce),
+		frame_system::CheckWeight::<runtime::Runtime>::new(),
+		pallet_transaction_payment::ChargeTransactionPayment::<runtime::Runtime>::from(0),
+	);
+
+	let raw_payload = runtime::SignedPayload::from_raw(
+		call.clone(),
+		extra.clone(),
+		(
+			(),
+			context.spec_version,
+			context.transaction_version,
+			context.genesis_hash,
+			context.best_hash,
+			(),
+			(),
+			(),
+		),
+	);
+	let signature = raw_payload.using_encoded(|e| sender.sign(e));
+
+	runtime::UncheckedExtrinsic::new_signed(
+		call,
+		sp_runtime::AccountId32::from(sender.public()).into(),
+		runtime::Signature::Sr25519(signature),
+		extra,
+	)
+}
@@ -0,0 +1,144 @@
+//! RPC interface for the rng-dao pallet.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpsee::{
+	core::{Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use serde::{de::DeserializeOwned, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use pallet_rng_dao_runtime_api::{
+	CycleDetails, GeneratorDetails, PredictedPayout, RandomnessRecord,
+	RngDaoApi as RngDaoRuntimeApi,
+};
+
+#[rpc(client, server)]
+pub trait RngDaoApi<BlockHash, AccountId, Balance, BlockNumber, CycleId, AssetId> {
+	#[method(name = "rngDao_cycle")]
+	fn cycle(
+		&self,
+		cycle_id: CycleId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<CycleDetails<AccountId, Balance, BlockNumber>>>;
+
+	#[method(name = "rngDao_generators")]
+	fn generators(
+		&self,
+		cycle_id: CycleId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<GeneratorDetails<AccountId>>>;
+
+	#[method(name = "rngDao_openCommitments")]
+	fn open_commitments(&self, who: AccountId, at: Option<BlockHash>) -> RpcResult<Vec<CycleId>>;
+
+	#[method(name = "rngDao_predictedPayout")]
+	fn predicted_payout(
+		&self,
+		cycle_id: CycleId,
+		who: AccountId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<PredictedPayout<AssetId, Balance>>>;
+
+	#[method(name = "rngDao_randomnessAt")]
+	fn randomness_at(
+		&self,
+		block: BlockNumber,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<RandomnessRecord<CycleId, BlockNumber>>>;
+}
+
+/// Provides RPC methods to query cycles of the rng-dao pallet.
+pub struct RngDao<C, Block> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, Block> RngDao<C, Block> {
+	/// Creates a new instance of the RngDao RPC helper.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+/// Error code for failures in the runtime.
+const RUNTIME_ERROR: i32 = 1;
+
+fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> JsonRpseeError {
+	CallError::Custom(ErrorObject::owned(
+		RUNTIME_ERROR,
+		"Runtime error",
+		Some(format!("{:?}", err)),
+	))
+	.into()
+}
+
+impl<C, Block, AccountId, Balance, BlockNumber, CycleId, AssetId>
+	RngDaoApiServer<<Block as BlockT>::Hash, AccountId, Balance, BlockNumber, CycleId, AssetId>
+	for RngDao<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: RngDaoRuntimeApi<Block, AccountId, Balance, BlockNumber, CycleId, AssetId>,
+	AccountId: Codec + Serialize + DeserializeOwned + Send + Sync + 'static,
+	Balance: Codec + Serialize + DeserializeOwned + Send + Sync + 'static,
+	BlockNumber: Codec + Serialize + DeserializeOwned + Send + Sync + 'static,
+	CycleId: Codec + Serialize + DeserializeOwned + Send + Sync + 'static,
+	AssetId: Codec + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+	fn cycle(
+		&self,
+		cycle_id: CycleId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<CycleDetails<AccountId, Balance, BlockNumber>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.cycle(&at, cycle_id).map_err(runtime_error_into_rpc_err)
+	}
+
+	fn generators(
+		&self,
+		cycle_id: CycleId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<GeneratorDetails<AccountId>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.generators(&at, cycle_id).map_err(runtime_error_into_rpc_err)
+	}
+
+	fn open_commitments(
+		&self,
+		who: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<CycleId>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.open_commitments(&at, who).map_err(runtime_error_into_rpc_err)
+	}
+
+	fn predicted_payout(
+		&self,
+		cycle_id: CycleId,
+		who: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<PredictedPayout<AssetId, Balance>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.predicted_payout(&at, cycle_id, who).map_err(runtime_error_into_rpc_err)
+	}
+
+	fn randomness_at(
+		&self,
+		block: BlockNumber,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<RandomnessRecord<CycleId, BlockNumber>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.randomness_at(&at, block).map_err(runtime_error_into_rpc_err)
+	}
+}
@@ -0,0 +1,32 @@
+//! Runtime API definition for the rng-dao pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+pub use pallet_rng_dao::{CycleDetails, GeneratorDetails, PredictedPayout, RandomnessRecord};
+
+sp_api::decl_runtime_apis! {
+	pub trait RngDaoApi<AccountId, Balance, BlockNumber, CycleId, AssetId> where
+		AccountId: Codec,
+		Balance: Codec,
+		BlockNumber: Codec,
+		CycleId: Codec,
+		AssetId: Codec,
+	{
+		/// Cycle with its phase and deadline blocks.
+		fn cycle(cycle_id: CycleId) -> Option<CycleDetails<AccountId, Balance, BlockNumber>>;
+		/// Generators of a cycle along with their reveal status.
+		fn generators(cycle_id: CycleId) -> Vec<GeneratorDetails<AccountId>>;
+		/// Cycles in which the account still has to reveal its secret.
+		fn open_commitments(who: AccountId) -> Vec<CycleId>;
+		/// Deposit and bounty share the generator gets for revealing its secret.
+		fn predicted_payout(
+			cycle_id: CycleId,
+			who: AccountId,
+		) -> Option<PredictedPayout<AssetId, Balance>>;
+		/// Random number of the latest cycle completed by the block, if it is still in history.
+		fn randomness_at(block: BlockNumber) -> Option<RandomnessRecord<CycleId, BlockNumber>>;
+	}
+}
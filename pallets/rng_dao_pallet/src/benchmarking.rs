@@ -2,15 +2,141 @@ use super::*;
 
 #[allow(unused)]
 use crate::Pallet as RngDao;
+use crate::{
+	commitment::{commitment, Salt, Secret, MAX_SECRET_LEN},
+	consumer::CallbackId,
+};
 use frame_benchmarking::{account, benchmarks, whitelist_account, whitelisted_caller};
-use frame_support::{assert_ok, traits::fungible::Mutate};
+use frame_support::{
+	assert_ok,
+	traits::{fungible::Mutate, fungibles, EnsureOrigin, Hooks},
+	BoundedVec,
+};
 use frame_system::RawOrigin;
+use sp_core::H256;
 use sp_runtime::traits::{Get, Hash, Keccak256, One};
+use sp_std::vec::Vec;
+
+const SEED: u32 = 0;
+const SALT: Salt = [7_u8; 32];
 
 fn assert_last_event<T: crate::Config>(generic_event: <T as crate::Config>::RuntimeEvent) {
 	frame_system::Pallet::<T>::assert_last_event(generic_event.into());
 }
 
+fn fund<T>(who: &T::AccountId)
+where
+	T: pallet_balances::Config + crate::Config,
+	<T as crate::Config>::Balance: From<u128>,
+	<T as pallet_balances::Config>::Balance: From<<T as crate::Config>::Balance>,
+{
+	let amount: <T as crate::Config>::Balance = 1_000_000_000_u128.into();
+	assert_ok!(<pallet_balances::Pallet<T> as Mutate<T::AccountId>>::mint_into(who, amount.into()));
+}
+
+/// Longest secrets, the worst case for hashing, distinct for every generator.
+fn secret(i: u32) -> Secret {
+	sp_std::vec![i as u8; MAX_SECRET_LEN as usize].try_into().unwrap()
+}
+
+/// Creates a cycle at block 1 with room for `generators` generators, completing with
+/// `min_revealers` reveals, by the whitelisted caller.
+fn create_cycle<T>(generators: u32, min_revealers: u32) -> (T::AccountId, T::CycleId)
+where
+	T: pallet_balances::Config + crate::Config,
+	<T as frame_system::Config>::BlockNumber: From<u32>,
+	<T as crate::Config>::Balance: From<u128>,
+	<T as pallet_balances::Config>::Balance: From<<T as crate::Config>::Balance>,
+{
+	frame_system::Pallet::<T>::set_block_number(1_u32.into());
+	let creator: T::AccountId = whitelisted_caller();
+	fund::<T>(&creator);
+	// slashed deposits go to the treasury, which has to exist to receive small amounts
+	fund::<T>(&T::TreasuryAccount::get());
+	let mut params = RngDao::<T>::default_cycle_parameters();
+	params.max_generators = (generators as u8).max(T::MinCycleParameters::get().max_generators);
+	params.min_revealers = (min_revealers as u8).max(T::MinCycleParameters::get().min_revealers);
+	let cycle_id = CycleCount::<T>::get();
+	assert_ok!(RngDao::<T>::create_new_rng_cycle(
+		RawOrigin::Signed(creator.clone()).into(),
+		1_000_000_u128.into(),
+		Some(params),
+		None
+	));
+	(creator, cycle_id)
+}
+
+/// Commits `generators` generators to cycle `cycle_id`, returns them along with their secrets.
+fn commit<T>(cycle_id: T::CycleId, generators: u32) -> Vec<(T::AccountId, Secret)>
+where
+	T: pallet_balances::Config + crate::Config,
+	<T as crate::Config>::Balance: From<u128>,
+	<T as pallet_balances::Config>::Balance: From<<T as crate::Config>::Balance>,
+{
+	(0..generators)
+		.map(|i| {
+			let generator: T::AccountId = account("generator", i, SEED);
+			fund::<T>(&generator);
+			let secret = secret(i);
+			let hash = commitment(&cycle_id, &generator, &SALT, &secret);
+			assert_ok!(RngDao::<T>::send_hash(
+				RawOrigin::Signed(generator.clone()).into(),
+				cycle_id,
+				hash
+			));
+			(generator, secret)
+		})
+		.collect()
+}
+
+/// Tops up the bounty of cycle `cycle_id` by `contributors` accounts other than its creator.
+fn contribute<T>(cycle_id: T::CycleId, contributors: u32)
+where
+	T: pallet_balances::Config + crate::Config,
+	<T as crate::Config>::Balance: From<u128>,
+	<T as pallet_balances::Config>::Balance: From<<T as crate::Config>::Balance>,
+{
+	for i in 0..contributors {
+		let contributor: T::AccountId = account("contributor", i, SEED);
+		fund::<T>(&contributor);
+		assert_ok!(RngDao::<T>::top_up_bounty(
+			RawOrigin::Signed(contributor).into(),
+			cycle_id,
+			1000_u128.into()
+		));
+	}
+}
+
+/// Moves to the second phase of cycle `cycle_id` and reveals the secrets of `generators`.
+fn reveal<T: crate::Config>(cycle_id: T::CycleId, generators: &[(T::AccountId, Secret)]) {
+	let cycle = Cycles::<T>::get(cycle_id).unwrap();
+	frame_system::Pallet::<T>::set_block_number(RngDao::<T>::second_phase_start(&cycle));
+	for (generator, secret) in generators {
+		assert_ok!(RngDao::<T>::reveal_secret(
+			RawOrigin::Signed(generator.clone()).into(),
+			cycle_id,
+			secret.clone(),
+			SALT
+		));
+	}
+}
+
+/// Moves to the deadline of cycle `cycle_id` and returns it.
+fn end_cycle<T: crate::Config>(cycle_id: T::CycleId) -> T::BlockNumber {
+	let deadline = RngDao::<T>::cycle_deadline(&Cycles::<T>::get(cycle_id).unwrap());
+	frame_system::Pallet::<T>::set_block_number(deadline);
+	deadline
+}
+
+/// Status a cycle with `revealed` reveals is finalised with.
+fn finalised_status(revealed: u32) -> CycleStatus {
+	if revealed == 0 {
+		CycleStatus::Failed
+	} else {
+		CycleStatus::Completed
+	}
+}
+
 benchmarks! {
 	where_clause { where
 		T: pallet_balances::Config
@@ -19,7 +145,10 @@ benchmarks! {
 		<T as frame_system::Config>::BlockNumber: From<u32>,
 		<T as crate::Config>::Balance: From<u128>,
 		<T as pallet_balances::Config>::Balance: From<<T as crate::Config>::Balance>,
-		<T as crate::Config>::CycleId: From<u128>
+		<T as crate::Config>::CycleId: From<u128>,
+		<T as crate::Config>::AssetId: From<u32>,
+		<T as crate::Config>::Assets: fungibles::Create<T::AccountId>
+		+ fungibles::Mutate<T::AccountId>
 	}
 
 	create_new_rng_cycle {
@@ -29,7 +158,7 @@ benchmarks! {
 		let bounty: <T as crate::Config>::Balance = 1000_u128.into();
 		let mint_amount: <T as crate::Config>::Balance = 10000_u128.into();
 		assert_ok!(<pallet_balances::Pallet::<T> as Mutate<T::AccountId>>::mint_into(&caller, mint_amount.into()));
-	}: _(RawOrigin::Signed(caller.clone()), bounty.clone())
+	}: _(RawOrigin::Signed(caller.clone()), bounty.clone(), None, None)
 	verify {
 		assert_eq!(
 			Cycles::<T>::get(cycle_id),
@@ -40,11 +169,53 @@ benchmarks! {
 					started: 1_u32.into(),
 					generators_count: 0_u8,
 					revealed_count: 0_u8,
-					random_number: 0_u64,
+					random_number: H256::zero(),
+					status: CycleStatus::CommitPhase,
+					params: CycleParameters {
+						deposit: RngDao::<T>::withholding_deposit(T::Deposit::get(), &bounty),
+						..RngDao::<T>::default_cycle_parameters()
+					},
+					commit_extensions: 0,
 				}
 			));
 	}
 
+	// bounty in an asset which is not sufficient, so the cycle's account has to be provided for
+	create_new_rng_cycle_with_asset {
+		frame_system::Pallet::<T>::set_block_number(1_u32.into());
+		let cycle_id = CycleCount::<T>::get();
+		let caller: T::AccountId = whitelisted_caller();
+		fund::<T>(&caller);
+		let asset: T::AssetId = 2_u32.into();
+		let bounty: <T as crate::Config>::Balance = 1000_u128.into();
+		assert_ok!(<T::Assets as fungibles::Create<T::AccountId>>::create(
+			asset,
+			caller.clone(),
+			false,
+			1_u128.into()
+		));
+		assert_ok!(<T::Assets as fungibles::Mutate<T::AccountId>>::mint_into(
+			asset,
+			&caller,
+			10000_u128.into()
+		));
+		assert_ok!(RngDao::<T>::set_min_asset_bounty(
+			T::AssetOrigin::successful_origin(),
+			asset,
+			Some(bounty.clone())
+		));
+	}: create_new_rng_cycle(RawOrigin::Signed(caller), bounty.clone(), None, Some(asset))
+	verify {
+		assert_eq!(BountyAssets::<T>::get(cycle_id), Some(asset));
+		assert_eq!(
+			<T::Assets as fungibles::Inspect<T::AccountId>>::balance(
+				asset,
+				&RngDao::<T>::account_id(&cycle_id)
+			),
+			bounty
+		);
+	}
+
 	send_hash {
 		frame_system::Pallet::<T>::set_block_number(1_u32.into());
 		let cycle_id : T::CycleId = 0_u128.into();
@@ -54,76 +225,182 @@ benchmarks! {
 
 		let mint_amount: <T as crate::Config>::Balance = 10000_u128.into();
 		assert_ok!(<pallet_balances::Pallet::<T> as Mutate<T::AccountId>>::mint_into(&origin, mint_amount.clone().into()));
-		assert_ok!(RngDao::<T>::create_new_rng_cycle(RawOrigin::Signed(origin.clone()).into(), bounty.clone()));
+		assert_ok!(RngDao::<T>::create_new_rng_cycle(RawOrigin::Signed(origin.clone()).into(), bounty.clone(), None, None));
 		let caller: T::AccountId = whitelisted_caller();
 		let deposit: <T as crate::Config>::Balance = 1000_u128.into();
 		assert_ok!(<pallet_balances::Pallet::<T> as Mutate<T::AccountId>>::mint_into(&caller, mint_amount.clone().into()));
 		let bytes = 1212_u64.to_le_bytes();
 		let hash = Keccak256::hash(&bytes);
 
-	}: _(RawOrigin::Signed(caller.clone()), cycle_id.clone(), hash.clone(), false)
+	}: _(RawOrigin::Signed(caller.clone()), cycle_id.clone(), hash.clone())
 	verify {
 		assert_last_event::<T>(crate::Event::<T>::HashReceived {cycle_id, sender: caller,
 		hash }.into());
 	}
 
+	// `r` secrets are revealed before, which the combined entropies grow with. The cycle needs
+	// this reveal to complete, which pays the `r` shares held back so far.
 	reveal_secret {
-		frame_system::Pallet::<T>::set_block_number(1_u32.into());
-		let cycle_id : T::CycleId = 0_u128.into();
-		let origin: T::AccountId = account("ALICE", 0_u32, 1_u32);
-		whitelist_account!(origin);
-		let bounty: <T as crate::Config>::Balance = 1000_u128.into();
-		let mint_amount: <T as crate::Config>::Balance = 10000_u128.into();
-		assert_ok!(<pallet_balances::Pallet::<T> as Mutate<T::AccountId>>::mint_into(&origin, mint_amount.clone().into()));
-		assert_ok!(RngDao::<T>::create_new_rng_cycle(RawOrigin::Signed(origin.clone()).into(), bounty.clone()));
-		let caller: T::AccountId = whitelisted_caller();
-		let deposit: <T as crate::Config>::Balance = 1000_u128.into();
-		assert_ok!(<pallet_balances::Pallet::<T> as Mutate<T::AccountId>>::mint_into(&caller, mint_amount.clone().into()));
-		let secret: u64 = 1212_u64;
-		let bytes = secret.to_le_bytes();
-		let hash = Keccak256::hash(&bytes);
-		assert_ok!(RngDao::<T>::send_hash(RawOrigin::Signed(caller.clone()).into(), cycle_id.clone(), hash, false));
-		frame_system::Pallet::<T>::set_block_number(
-			<T as frame_system::Config>::BlockNumber::one()
-			+ <T as crate::Config>::DelayBeforeBots::get()
-			+ <T as crate::Config>::DelayBeforeSecondPhase::get()
-			+ <T as frame_system::Config>::BlockNumber::one()
-		);
-
-	}: _(RawOrigin::Signed(caller.clone()), cycle_id.clone(), secret, false)
+		let r in 0 .. T::MaxCycleParameters::get().max_generators as u32 - 1;
+		let (_, cycle_id) = create_cycle::<T>(r + 1, r + 1);
+		let mut generators = commit::<T>(cycle_id, r + 1);
+		let (caller, secret) = generators.pop().unwrap();
+		whitelist_account!(caller);
+		reveal::<T>(cycle_id, &generators);
+	}: _(RawOrigin::Signed(caller.clone()), cycle_id, secret, SALT)
 	verify {
 		assert_last_event::<T>(crate::Event::<T>::SecretReceived {cycle_id, sender: caller}.into());
 	}
 
-	// NOTE: get_random_number does more work when cycle fails as it has to return bounty
+	// `u` generators did not reveal and have their deposit slashed, `r` did and their secrets are
+	// combined. `r` = 0 is the failing path, where the bounty is also returned to the creator.
 	get_random_number {
-		frame_system::Pallet::<T>::set_block_number(1_u32.into());
-		let cycle_id : T::CycleId = 0_u128.into();
-		let caller: T::AccountId = whitelisted_caller();
-		let origin: T::AccountId = account("ALICE", 0_u32, 1_u32);
-		whitelist_account!(origin);
-		let bounty: <T as crate::Config>::Balance = 1000_u128.into();
-		let mint_amount: <T as crate::Config>::Balance = 10000_u128.into();
-		assert_ok!(<pallet_balances::Pallet::<T> as Mutate<T::AccountId>>::mint_into(&caller, mint_amount.clone().into()));
-		assert_ok!(RngDao::<T>::create_new_rng_cycle(RawOrigin::Signed(caller.clone()).into(), bounty.clone()));
-		let deposit: <T as crate::Config>::Balance = 1000_u128.into();
-		assert_ok!(<pallet_balances::Pallet::<T> as Mutate<T::AccountId>>::mint_into(&origin, mint_amount.clone().into()));
-		let secret: u64 = 1212_u64;
-		let bytes = secret.to_le_bytes();
-		let hash = Keccak256::hash(&bytes);
-		assert_ok!(RngDao::<T>::send_hash(RawOrigin::Signed(origin.clone()).into(), cycle_id.clone(), hash, false));
-		// not revealing the secret so that cycle fails, and bounty will be returned
-		frame_system::Pallet::<T>::set_block_number(
-			<T as frame_system::Config>::BlockNumber::one()
-			+ <T as crate::Config>::DelayBeforeBots::get()
-			+ <T as crate::Config>::DelayBeforeSecondPhase::get()
-			+ <T as crate::Config>::SecondPhaseDuration::get()
-			+ <T as frame_system::Config>::BlockNumber::one()
+		let u in 0 .. T::MaxCycleParameters::get().max_generators as u32 / 2;
+		let r in 0 .. T::MaxCycleParameters::get().max_generators as u32 / 2;
+		let (caller, cycle_id) = create_cycle::<T>(u + r, 1);
+		let generators = commit::<T>(cycle_id, u + r);
+		reveal::<T>(cycle_id, &generators[..r as usize]);
+		end_cycle::<T>(cycle_id);
+	}: _(RawOrigin::Signed(caller), cycle_id)
+	verify {
+		assert_eq!(Cycles::<T>::get(cycle_id).unwrap().status, finalised_status(r));
+	}
+
+	// same as `get_random_number`, when the cycle is finalised at its deadline
+	finalise_cycle {
+		let u in 0 .. T::MaxCycleParameters::get().max_generators as u32 / 2;
+		let r in 0 .. T::MaxCycleParameters::get().max_generators as u32 / 2;
+		let (_, cycle_id) = create_cycle::<T>(u + r, 1);
+		let generators = commit::<T>(cycle_id, u + r);
+		reveal::<T>(cycle_id, &generators[..r as usize]);
+		let deadline = end_cycle::<T>(cycle_id);
+	}: {
+		RngDao::<T>::on_initialize(deadline);
+	}
+	verify {
+		assert_eq!(Cycles::<T>::get(cycle_id).unwrap().status, finalised_status(r));
+	}
+
+	// `u` commitments of generators who did not reveal and `r` secrets are removed
+	reap_cycle {
+		let u in 0 .. T::MaxCycleParameters::get().max_generators as u32 / 2;
+		let r in 0 .. T::MaxCycleParameters::get().max_generators as u32 / 2;
+		let (caller, cycle_id) = create_cycle::<T>(u + r, 1);
+		let generators = commit::<T>(cycle_id, u + r);
+		reveal::<T>(cycle_id, &generators[..r as usize]);
+		let deadline = end_cycle::<T>(cycle_id);
+		assert_ok!(RngDao::<T>::get_random_number(RawOrigin::Signed(caller.clone()).into(), cycle_id));
+		frame_system::Pallet::<T>::set_block_number(deadline + T::RetentionPeriod::get());
+	}: _(RawOrigin::Signed(caller), cycle_id)
+	verify {
+		assert!(Cycles::<T>::get(cycle_id).is_none());
+		assert_last_event::<T>(crate::Event::<T>::CycleReaped { cycle_id }.into());
+	}
+
+	// `n` queued requests of a consumer no longer in the runtime are dropped, the weight of
+	// consumers which are called is added to this by `on_initialize`
+	deliver_randomness {
+		let n in 0 .. T::MaxPendingRequests::get();
+		let callback: CallbackId = *b"unknown_";
+		let queue: Vec<_> = (0..u64::from(n))
+			.map(|id| (RandomnessRequest { id, callback }, H256::zero()))
+			.collect();
+		QueuedCallbacks::<T>::put(BoundedVec::try_from(queue).unwrap());
+		PendingRequests::<T>::put(n);
+		ConsumerRequests::<T>::insert(callback, n);
+	}: {
+		RngDao::<T>::deliver_randomness(T::MaxCallbackWeight::get());
+	}
+	verify {
+		assert!(QueuedCallbacks::<T>::get().is_empty());
+		assert_eq!(PendingRequests::<T>::get(), 0);
+		assert!(!ConsumerRequests::<T>::contains_key(callback));
+	}
+
+	register_bot {
+		let origin = T::BotOrigin::successful_origin();
+		let bot: T::AccountId = account("BOT", 0_u32, 1_u32);
+	}: _<T::RuntimeOrigin>(origin, bot.clone())
+	verify {
+		assert_last_event::<T>(crate::Event::<T>::BotRegistered { bot }.into());
+	}
+
+	deregister_bot {
+		let origin = T::BotOrigin::successful_origin();
+		let bot: T::AccountId = account("BOT", 0_u32, 1_u32);
+		Bots::<T>::insert(&bot, ());
+	}: _<T::RuntimeOrigin>(origin, bot.clone())
+	verify {
+		assert_last_event::<T>(crate::Event::<T>::BotDeregistered { bot }.into());
+	}
+
+	set_bot_share_policy {
+		let origin = T::BotOrigin::successful_origin();
+		let policy = BotShare::Flat(10_u128.into());
+	}: _<T::RuntimeOrigin>(origin, policy.clone())
+	verify {
+		assert_last_event::<T>(crate::Event::<T>::BotSharePolicySet { policy }.into());
+	}
+
+	set_min_asset_bounty {
+		let origin = T::AssetOrigin::successful_origin();
+		let asset: T::AssetId = 1_u32.into();
+		let min_bounty: Option<<T as crate::Config>::Balance> = Some(100_u128.into());
+	}: _<T::RuntimeOrigin>(origin, asset, min_bounty.clone())
+	verify {
+		assert_last_event::<T>(crate::Event::<T>::MinAssetBountySet { asset, min_bounty }.into());
+	}
+
+	// without contributors, their refunds are added by `refund_bounty`
+	cancel_cycle {
+		let (caller, cycle_id) = create_cycle::<T>(1, 1);
+	}: _(RawOrigin::Signed(caller.clone()), cycle_id)
+	verify {
+		assert_last_event::<T>(crate::Event::<T>::CycleCancelled { cycle_id, creator: caller }.into());
+	}
+
+	// `c` contributors and the creator are refunded, added to the weight of cancelling and
+	// finalising cycles
+	refund_bounty {
+		let c in 0 .. T::MaxContributors::get();
+		let (_, cycle_id) = create_cycle::<T>(1, 1);
+		contribute::<T>(cycle_id, c);
+		let cycle = Cycles::<T>::get(cycle_id).unwrap();
+	}: {
+		assert_ok!(RngDao::<T>::refund_bounty(&cycle_id, &cycle));
+	}
+	verify {
+		for i in 0..c {
+			let contributor: T::AccountId = account("contributor", i, SEED);
+			let funded: <T as crate::Config>::Balance = 1_000_000_000_u128.into();
+			assert_eq!(pallet_balances::Pallet::<T>::free_balance(&contributor), funded.into());
+		}
+	}
+
+	// topped up by a new contributor when there is room for only one more
+	top_up_bounty {
+		let (_, cycle_id) = create_cycle::<T>(1, 1);
+		contribute::<T>(cycle_id, T::MaxContributors::get().saturating_sub(1));
+		let caller: T::AccountId = account("top-up", 0, SEED);
+		whitelist_account!(caller);
+		fund::<T>(&caller);
+		let amount: <T as crate::Config>::Balance = 1000_u128.into();
+	}: _(RawOrigin::Signed(caller.clone()), cycle_id, amount.clone())
+	verify {
+		assert_last_event::<T>(
+			crate::Event::<T>::BountyToppedUp { cycle_id, sender: caller, amount }.into()
 		);
+	}
 
-	}: _(RawOrigin::Signed(caller.clone()), cycle_id.clone())
+	set_parameters {
+		let origin = T::AdminOrigin::successful_origin();
+		let parameters = Some(Parameters {
+			min_bounty: T::MinBounty::get(),
+			cycle_parameters: RngDao::<T>::default_cycle_parameters(),
+		});
+	}: _<T::RuntimeOrigin>(origin, parameters.clone())
 	verify {
-		assert_last_event::<T>(crate::Event::<T>::CycleFailed {cycle_id, creator: caller}.into());
+		assert_last_event::<T>(crate::Event::<T>::ParametersUpdated { parameters }.into());
 	}
 
 	impl_benchmark_test_suite!(RngDao, crate::mock::ExtBuilder::default().build(), crate::mock::Test);
@@ -0,0 +1,64 @@
+//! Ways of combining the revealed secrets of a cycle into its random number.
+//!
+//! Every revealed secret is first turned into 32 bytes of entropy, see
+//! [`crate::commitment::CommitmentVersion::entropy`]. Once the cycle is finalised the runtime's
+//! [`Combiner`] combines them, in the order they were revealed, into the cycle's random number.
+
+use core::marker::PhantomData;
+use sp_core::H256;
+use sp_runtime::traits::{BlakeTwo256, Hash, Keccak256};
+use sp_std::vec::Vec;
+
+pub trait Combiner {
+	/// Random number of a cycle whose secrets have `entropies`, in reveal order. Only called with
+	/// at least one entropy.
+	fn combine(entropies: &[H256]) -> H256;
+}
+
+/// XOR of all entropies. Cheapest one, but the last generator to reveal knows the outcome of
+/// revealing as well as of not revealing.
+pub struct Xor;
+
+impl Combiner for Xor {
+	fn combine(entropies: &[H256]) -> H256 {
+		entropies.iter().fold(H256::zero(), |acc, entropy| acc ^ *entropy)
+	}
+}
+
+/// `acc = H(acc ‖ entropy)` over all entropies, starting with `acc` of zero. Unlike [`Xor`] no
+/// generator can cancel out contributions of others by choosing its secret.
+pub struct HashChain<H>(PhantomData<H>);
+
+impl<H: Hash<Output = H256>> Combiner for HashChain<H> {
+	fn combine(entropies: &[H256]) -> H256 {
+		entropies.iter().fold(H256::zero(), |acc, entropy| H::hash_of(&(acc, entropy)))
+	}
+}
+
+pub type KeccakChain = HashChain<Keccak256>;
+pub type Blake2Chain = HashChain<BlakeTwo256>;
+
+/// `H(root)` of the binary Merkle tree with entropies as leaves. A node without a sibling is
+/// promoted to the next level as is.
+pub struct MerkleRoot<H>(PhantomData<H>);
+
+impl<H: Hash<Output = H256>> Combiner for MerkleRoot<H> {
+	fn combine(entropies: &[H256]) -> H256 {
+		let mut level: Vec<H256> = entropies.to_vec();
+		while level.len() > 1 {
+			level = level
+				.chunks(2)
+				.map(|pair| match pair {
+					[left, right] => H::hash_of(&(left, right)),
+					// last node of an odd level
+					_ => pair[0],
+				})
+				.collect();
+		}
+		let root = level.first().copied().unwrap_or_default();
+		H::hash(root.as_bytes())
+	}
+}
+
+pub type KeccakMerkleRoot = MerkleRoot<Keccak256>;
+pub type Blake2MerkleRoot = MerkleRoot<BlakeTwo256>;
@@ -0,0 +1,77 @@
+//! Commitments of generators to their secrets.
+//!
+//! A generator sends [`commitment`] of its secret with `send_hash` and later reveals the secret
+//! along with the salt with `reveal_secret`. Salting stops observers from brute-forcing short
+//! or guessable secrets, and binding the commitment to the cycle and the generator's account
+//! stops a hash from being copied to another cycle or by another generator.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{traits::ConstU32, BoundedVec, RuntimeDebug};
+use scale_info::TypeInfo;
+use sp_core::H256;
+use sp_runtime::traits::{Hash, Keccak256};
+
+/// Maximum length of a secret in bytes.
+pub const MAX_SECRET_LEN: u32 = 32;
+
+pub type Secret = BoundedVec<u8, ConstU32<MAX_SECRET_LEN>>;
+pub type Salt = [u8; 32];
+
+/// Scheme a commitment was made with.
+#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, Clone, Copy, PartialEq, Eq, TypeInfo)]
+pub enum CommitmentVersion {
+	/// `keccak256(secret)` of a `u64` secret as 8 little-endian bytes, without salt. Only used
+	/// by commitments made before salted secrets were introduced.
+	Legacy,
+	/// `keccak256(SCALE((cycle_id, account, salt, secret)))`, see [`commitment`].
+	Salted,
+}
+
+impl CommitmentVersion {
+	/// Commitment of `secret` under this scheme. `Legacy` ignores `cycle_id`, `account` and
+	/// `salt`.
+	pub fn commitment<CycleId: Encode, AccountId: Encode>(
+		&self,
+		cycle_id: &CycleId,
+		account: &AccountId,
+		salt: &Salt,
+		secret: &[u8],
+	) -> H256 {
+		match self {
+			CommitmentVersion::Legacy => Keccak256::hash(secret),
+			CommitmentVersion::Salted => Keccak256::hash_of(&(cycle_id, account, salt, secret)),
+		}
+	}
+
+	/// Contribution of a revealed `secret` to the random number of its cycle. `Legacy` secrets
+	/// are used as is, zero padded, so that XOR of them matches the `u64` random numbers of
+	/// cycles from before.
+	pub fn entropy(&self, secret: &[u8]) -> H256 {
+		match self {
+			CommitmentVersion::Legacy => {
+				let mut entropy = H256::zero();
+				let len = secret.len().min(H256::len_bytes());
+				entropy[..len].copy_from_slice(&secret[..len]);
+				entropy
+			},
+			CommitmentVersion::Salted => Keccak256::hash(secret),
+		}
+	}
+}
+
+/// Commitment to send with `send_hash` for revealing `secret` and `salt` in cycle `cycle_id`
+/// from `account`. It is the Keccak256 hash of the SCALE encoded tuple, i.e. the encoded cycle
+/// id, account and salt followed by the compact length prefixed secret.
+pub fn commitment<CycleId: Encode, AccountId: Encode>(
+	cycle_id: &CycleId,
+	account: &AccountId,
+	salt: &Salt,
+	secret: &[u8],
+) -> H256 {
+	CommitmentVersion::Salted.commitment(cycle_id, account, salt, secret)
+}
+
+/// Commitment of a `u64` secret under the `Legacy` scheme.
+pub fn legacy_commitment(secret: u64) -> H256 {
+	CommitmentVersion::Legacy.commitment(&(), &(), &[0_u8; 32], &secret.to_le_bytes())
+}
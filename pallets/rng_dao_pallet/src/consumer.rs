@@ -0,0 +1,58 @@
+//! Delivery of random numbers to other pallets.
+//!
+//! A pallet needing randomness calls [`crate::Pallet::request_randomness`] with the id of a cycle
+//! and a [`CallbackId`] it goes by. Once the cycle completes, the runtime's [`RandomnessConsumer`]s
+//! are called with the cycle's random number in `on_initialize`, as long as their weight fits in
+//! `MaxCallbackWeight` of the block. The others are queued for the next blocks. Requests of a
+//! consumer whose `max_weight` no longer fits in `MaxCallbackWeight` at all are dropped.
+
+use frame_support::weights::Weight;
+use sp_core::H256;
+
+/// Identifier of a consumer, e.g. the `PalletId` of the consuming pallet.
+pub type CallbackId = [u8; 8];
+
+/// Identifier of a request for randomness, unique across all consumers.
+pub type RequestId = u64;
+
+/// Pallets acting on random numbers of cycles. Implemented for tuples, the runtime configures all
+/// of its consumers as one, and every consumer only handles requests made with its own
+/// [`CallbackId`].
+pub trait RandomnessConsumer {
+	/// Upper bound of the weight of `on_randomness` for requests made with `callback`, `None`
+	/// if this is not the consumer going by `callback`.
+	fn max_weight(callback: &CallbackId) -> Option<Weight>;
+
+	/// Acts on `random_number` requested with `request_id`. Returns the weight used, `None` if
+	/// this is not the consumer going by `callback`.
+	fn on_randomness(
+		callback: &CallbackId,
+		request_id: RequestId,
+		random_number: H256,
+	) -> Option<Weight>;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl RandomnessConsumer for Tuple {
+	fn max_weight(callback: &CallbackId) -> Option<Weight> {
+		for_tuples!( #(
+			if let Some(weight) = Tuple::max_weight(callback) {
+				return Some(weight)
+			}
+		)* );
+		None
+	}
+
+	fn on_randomness(
+		callback: &CallbackId,
+		request_id: RequestId,
+		random_number: H256,
+	) -> Option<Weight> {
+		for_tuples!( #(
+			if let Some(weight) = Tuple::on_randomness(callback, request_id, random_number) {
+				return Some(weight)
+			}
+		)* );
+		None
+	}
+}
@@ -0,0 +1,183 @@
+//! Where the bounty and the deposits of a cycle are kept while it runs.
+//!
+//! [`CycleAccount`] transfers them to an account of the cycle derived from the pallet id.
+//! [`NamedReserves`] keeps them reserved on the accounts they belong to instead, so that they stay
+//! visible in wallets and no account has to be created for a cycle.
+
+use crate::Config;
+use codec::Encode;
+use core::marker::PhantomData;
+use frame_support::{
+	dispatch::DispatchResult,
+	ensure,
+	traits::{
+		fungible::{Inspect, Transfer},
+		BalanceStatus, Get, NamedReservableCurrency,
+	},
+};
+use sp_runtime::{
+	traits::{Saturating, Zero},
+	DispatchError, TokenError,
+};
+
+pub trait CycleFunds<T: Config> {
+	/// Set `amount` of `who` aside for cycle `cycle_id`.
+	fn hold(cycle_id: &T::CycleId, who: &T::AccountId, amount: T::Balance) -> DispatchResult;
+
+	/// Set `amount` of `who` aside for cycle `cycle_id` as if `owner` had set it aside, so that it
+	/// is paid out as funds of `owner`.
+	fn hold_for(
+		cycle_id: &T::CycleId,
+		who: &T::AccountId,
+		owner: &T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult;
+
+	/// Pay `amount` set aside by `from` for cycle `cycle_id` to `to`, which may be `from` itself.
+	fn pay(
+		cycle_id: &T::CycleId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult;
+
+	/// Pay whatever is still set aside by `from` for cycle `cycle_id` to `to`, and return the
+	/// amount paid. Only called once the cycle is finalised, after all other payments.
+	fn sweep(
+		cycle_id: &T::CycleId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+	) -> Result<T::Balance, DispatchError>;
+
+	/// Total set aside for cycle `cycle_id` by `holders`, checked by `try_state`.
+	#[cfg(any(feature = "try-runtime", test))]
+	fn held(cycle_id: &T::CycleId, holders: &[T::AccountId]) -> T::Balance;
+}
+
+/// Transfers the funds of a cycle to its own account, with `Config::Balances`. Funds are pooled
+/// there, so `from` is not needed to pay them out and [`CycleFunds::sweep`] empties the account.
+pub struct CycleAccount;
+
+impl<T: Config> CycleFunds<T> for CycleAccount {
+	fn hold(cycle_id: &T::CycleId, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		T::Balances::transfer(who, &crate::Pallet::<T>::account_id(cycle_id), amount, true)?;
+		Ok(())
+	}
+
+	fn hold_for(
+		cycle_id: &T::CycleId,
+		who: &T::AccountId,
+		_owner: &T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		<Self as CycleFunds<T>>::hold(cycle_id, who, amount)
+	}
+
+	fn pay(
+		cycle_id: &T::CycleId,
+		_from: &T::AccountId,
+		to: &T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		T::Balances::transfer(&crate::Pallet::<T>::account_id(cycle_id), to, amount, false)?;
+		Ok(())
+	}
+
+	fn sweep(
+		cycle_id: &T::CycleId,
+		_from: &T::AccountId,
+		to: &T::AccountId,
+	) -> Result<T::Balance, DispatchError> {
+		let cycle_account = crate::Pallet::<T>::account_id(cycle_id);
+		let total = T::Balances::balance(&cycle_account);
+		if total.is_zero() {
+			return Ok(total)
+		}
+		T::Balances::transfer(&cycle_account, to, total, false)
+	}
+
+	/// Everything in the cycle's account, whoever set it aside.
+	#[cfg(any(feature = "try-runtime", test))]
+	fn held(cycle_id: &T::CycleId, _holders: &[T::AccountId]) -> T::Balance {
+		T::Balances::balance(&crate::Pallet::<T>::account_id(cycle_id))
+	}
+}
+
+/// Reserves the funds of a cycle on the accounts they belong to, under a reserve named after the
+/// cycle, see [`NamedReserves::reserve_id`]. Payments to other accounts repatriate the reserved
+/// funds, which requires the receiving account, e.g. the treasury, to exist.
+///
+/// An account needs a named reserve for every cycle it takes part in, so `MaxReserves` of the
+/// currency bounds the number of cycles an account can take part in at once.
+pub struct NamedReserves<C>(PhantomData<C>);
+
+impl<C> NamedReserves<C> {
+	/// Name of the reserves of cycle `cycle_id`, derived from the pallet id and the cycle id.
+	pub fn reserve_id<T: Config>(cycle_id: &T::CycleId) -> [u8; 8] {
+		let hash = sp_io::hashing::blake2_128(&(T::PalletId::get(), cycle_id).encode());
+		let mut id = [0_u8; 8];
+		id.copy_from_slice(&hash[..8]);
+		id
+	}
+}
+
+impl<T: Config, C> CycleFunds<T> for NamedReserves<C>
+where
+	C: NamedReservableCurrency<T::AccountId, Balance = T::Balance>,
+	C::ReserveIdentifier: From<[u8; 8]>,
+{
+	fn hold(cycle_id: &T::CycleId, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		C::reserve_named(&Self::reserve_id::<T>(cycle_id).into(), who, amount)
+	}
+
+	/// Reserves `amount` on `who` and moves it to the reserve of `owner`, which has to exist.
+	fn hold_for(
+		cycle_id: &T::CycleId,
+		who: &T::AccountId,
+		owner: &T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let id = Self::reserve_id::<T>(cycle_id).into();
+		C::reserve_named(&id, who, amount.clone())?;
+		if who != owner {
+			let missing =
+				C::repatriate_reserved_named(&id, who, owner, amount, BalanceStatus::Reserved)?;
+			ensure!(missing.is_zero(), TokenError::NoFunds);
+		}
+		Ok(())
+	}
+
+	fn pay(
+		cycle_id: &T::CycleId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let id = Self::reserve_id::<T>(cycle_id).into();
+		let missing = if from == to {
+			C::unreserve_named(&id, from, amount)
+		} else {
+			C::repatriate_reserved_named(&id, from, to, amount, BalanceStatus::Free)?
+		};
+		ensure!(missing.is_zero(), TokenError::NoFunds);
+		Ok(())
+	}
+
+	fn sweep(
+		cycle_id: &T::CycleId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+	) -> Result<T::Balance, DispatchError> {
+		let amount = C::reserved_balance_named(&Self::reserve_id::<T>(cycle_id).into(), from);
+		<Self as CycleFunds<T>>::pay(cycle_id, from, to, amount.clone())?;
+		Ok(amount)
+	}
+
+	#[cfg(any(feature = "try-runtime", test))]
+	fn held(cycle_id: &T::CycleId, holders: &[T::AccountId]) -> T::Balance {
+		let id = Self::reserve_id::<T>(cycle_id).into();
+		holders.iter().fold(Zero::zero(), |held: T::Balance, holder| {
+			held.saturating_add(C::reserved_balance_named(&id, holder))
+		})
+	}
+}
@@ -1,6 +1,7 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub use pallet::*;
+pub use weights::WeightInfo;
 
 #[cfg(test)]
 mod mock;
@@ -11,59 +12,297 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+pub mod combiner;
+pub mod commitment;
+pub mod consumer;
+pub mod funds;
+pub mod migrations;
 pub mod weights;
+pub mod withholding;
 
-//NOTE: Generate hash of secret number (u64) based on its little_endian representation as array of
-//u8 (byte)
+use sp_core::crypto::KeyTypeId;
+
+/// Key type of the keys the off-chain worker signs bot transactions with. Keys are inserted with
+/// `key insert --key-type rngd --scheme sr25519`.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"rngd");
+
+/// Crypto of the `rngd` keys.
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_runtime::{
+		app_crypto::{app_crypto, sr25519},
+		MultiSignature, MultiSigner,
+	};
+	app_crypto!(sr25519, KEY_TYPE);
+
+	pub struct AuthId;
+
+	impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for AuthId {
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+}
 
 #[frame_support::pallet]
 pub mod pallet {
-	use crate::weights::RngDaoWeightInfo;
+	use crate::{
+		combiner::Combiner,
+		commitment::{CommitmentVersion, Salt, Secret},
+		consumer::{CallbackId, RandomnessConsumer, RequestId},
+		funds::CycleFunds,
+		weights::WeightInfo,
+		withholding::Withholding,
+	};
 	use codec::FullCodec;
 	use core::fmt::Debug;
 	use frame_support::{
-		ensure, pallet_prelude::*, traits::fungible::Transfer, PalletId, RuntimeDebug,
+		ensure,
+		pallet_prelude::*,
+		storage::with_storage_layer,
+		traits::{
+			fungible::Transfer,
+			fungibles::{self, Inspect as _, Transfer as _},
+			tokens::DepositConsequence,
+			ConstU32, Randomness,
+		},
+		PalletId, RuntimeDebug,
 	};
-	use frame_system::pallet_prelude::*;
+	use frame_system::{
+		offchain::{AppCrypto, CreateSignedTransaction, SendSignedTransaction, Signer},
+		pallet_prelude::*,
+	};
+	#[cfg(feature = "std")]
+	use serde::{Deserialize, Serialize};
 	use sp_core::H256;
 	use sp_runtime::{
+		offchain::{
+			storage::StorageValueRef,
+			storage_lock::{StorageLock, Time},
+		},
 		traits::{
 			AccountIdConversion, AtLeast32BitUnsigned, CheckedAdd, CheckedDiv, CheckedSub, Hash,
-			Keccak256, One, Zero,
+			Keccak256, MaybeSerializeDeserialize, One, Saturating, Zero,
 		},
-		ArithmeticError, SaturatedConversion,
+		ArithmeticError, Perbill, SaturatedConversion,
 	};
+	use sp_std::vec::Vec;
 
+	/// Lifecycle of a cycle. Time based phases are advanced lazily by the extrinsics touching the
+	/// cycle, `Completed` and `Failed` are final and only reached through `get_random_number`,
+	/// `Cancelled` through `cancel_cycle`.
+	#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, Clone, Copy, PartialEq, Eq, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum CycleStatus {
+		/// Only human generators can commit a hash.
+		CommitPhase,
+		/// Bots can commit a hash along with human generators.
+		BotCommitPhase,
+		/// Generators reveal their secrets.
+		RevealPhase,
+		/// All deadlines have passed, cycle is waiting to be finalised.
+		Finished,
+		/// Finalised with at least one revealed secret.
+		Completed,
+		/// Finalised without any revealed secret, bounty is returned to creator.
+		Failed,
+		/// Cancelled by creator before anyone committed, bounty is returned to creator.
+		Cancelled,
+	}
+
+	impl CycleStatus {
+		pub fn is_finalised(&self) -> bool {
+			matches!(self, CycleStatus::Completed | CycleStatus::Failed | CycleStatus::Cancelled)
+		}
+	}
+
+	/// Timing and participation parameters of a cycle, fixed when the cycle is created.
 	#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, Clone, PartialEq, Eq, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub struct CycleParameters<Balance, BlockNumber> {
+		pub delay_before_bots: BlockNumber,
+		pub delay_before_second_phase: BlockNumber,
+		pub second_phase_duration: BlockNumber,
+		pub max_generators: u8,
+		pub deposit: Balance,
+		/// Revealed secrets needed for the cycle to complete, it fails with fewer of them.
+		pub min_revealers: u8,
+	}
+
+	impl<Balance: PartialOrd, BlockNumber: PartialOrd> CycleParameters<Balance, BlockNumber> {
+		/// Whether every parameter lies between the ones of `min` and `max`, both inclusive.
+		pub fn is_within(&self, min: &Self, max: &Self) -> bool {
+			min.delay_before_bots <= self.delay_before_bots &&
+				self.delay_before_bots <= max.delay_before_bots &&
+				min.delay_before_second_phase <= self.delay_before_second_phase &&
+				self.delay_before_second_phase <= max.delay_before_second_phase &&
+				min.second_phase_duration <= self.second_phase_duration &&
+				self.second_phase_duration <= max.second_phase_duration &&
+				min.max_generators <= self.max_generators &&
+				self.max_generators <= max.max_generators &&
+				min.deposit <= self.deposit &&
+				self.deposit <= max.deposit &&
+				min.min_revealers <= self.min_revealers &&
+				self.min_revealers <= max.min_revealers
+		}
+	}
+
+	/// Parameters set by `AdminOrigin`, overriding `MinBounty` and the constants of the default
+	/// cycle parameters.
+	#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, Clone, PartialEq, Eq, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub struct Parameters<Balance, BlockNumber> {
+		pub min_bounty: Balance,
+		/// Parameters of cycles created without their own ones.
+		pub cycle_parameters: CycleParameters<Balance, BlockNumber>,
+	}
+
+	impl<Balance: PartialOrd + Zero, BlockNumber: PartialOrd + Zero> Parameters<Balance, BlockNumber> {
+		/// Whether cycles can be created with these parameters: bounty, deposit and every delay
+		/// are non-zero, there is room for the revealers among the generators, and the cycle
+		/// parameters lie between `min` and `max`.
+		pub fn is_valid(
+			&self,
+			min: &CycleParameters<Balance, BlockNumber>,
+			max: &CycleParameters<Balance, BlockNumber>,
+		) -> bool {
+			let params = &self.cycle_parameters;
+			!self.min_bounty.is_zero() &&
+				!params.deposit.is_zero() &&
+				!params.delay_before_bots.is_zero() &&
+				!params.delay_before_second_phase.is_zero() &&
+				!params.second_phase_duration.is_zero() &&
+				params.min_revealers <= params.max_generators &&
+				params.is_within(min, max)
+		}
+	}
+
+	#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, Clone, PartialEq, Eq, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 	pub struct RngCycle<AccountId, Balance, BlockNumber, RandomNumber> {
 		pub creator: AccountId,
 		pub bounty: Balance,
 		pub started: BlockNumber,
-		/// random_number is only valid if Status is `CompletedWithSuccess`
+		/// random_number is only valid if status is `CycleStatus::Completed`
 		pub random_number: RandomNumber,
 		pub generators_count: u8,
 		pub revealed_count: u8,
+		pub status: CycleStatus,
+		pub params: CycleParameters<Balance, BlockNumber>,
+		/// Times the commit phase was extended for lack of commitments.
+		pub commit_extensions: u8,
+	}
+
+	/// Share of the bounty paid to a bot revealing its secret, on top of its deposit.
+	#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, Clone, PartialEq, Eq, TypeInfo)]
+	pub enum BotShare<Balance> {
+		/// Same share as human generators.
+		Equal,
+		/// Given part of the share of human generators.
+		Reduced(Perbill),
+		/// Flat fee, at most the share of human generators.
+		Flat(Balance),
+	}
+
+	impl<Balance> Default for BotShare<Balance> {
+		fn default() -> Self {
+			BotShare::Equal
+		}
 	}
 
 	#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, Clone, PartialEq, Eq, TypeInfo)]
 	pub struct Generator {
-		secret: u64,
-		hash: H256,
-		is_bot: bool,
+		pub hash: H256,
+		pub is_bot: bool,
+		/// Scheme `hash` was computed with.
+		pub version: CommitmentVersion,
+	}
+
+	/// Commitment of the off-chain worker's bot to a cycle, kept in local off-chain storage until
+	/// the cycle is over.
+	#[derive(Encode, Decode)]
+	pub(crate) struct BotCommitment<AccountId, Public> {
+		pub account: AccountId,
+		pub public: Public,
+		pub secret: Secret,
+		pub salt: Salt,
+	}
+
+	/// Cycle along with its phase at the queried block, as returned by the runtime API.
+	#[derive(RuntimeDebug, Encode, Decode, Clone, PartialEq, Eq, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub struct CycleDetails<AccountId, Balance, BlockNumber> {
+		pub cycle: RngCycle<AccountId, Balance, BlockNumber, H256>,
+		pub phase: CycleStatus,
+		/// First block in which secrets can be revealed.
+		pub second_phase_start: BlockNumber,
+		/// First block in which the cycle can be finalised.
+		pub deadline: BlockNumber,
+	}
+
+	/// Random number of a completed cycle along with the block in which it became known.
+	#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, Clone, PartialEq, Eq, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub struct RandomnessRecord<CycleId, BlockNumber> {
+		pub cycle_id: CycleId,
+		pub block: BlockNumber,
+		pub random_number: H256,
+	}
+
+	/// What a generator gets for revealing its secret in time, as returned by the runtime API.
+	#[derive(RuntimeDebug, Encode, Decode, Clone, PartialEq, Eq, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub struct PredictedPayout<AssetId, Balance> {
+		/// Deposit paid back, always in the native token.
+		pub deposit: Balance,
+		/// Share from bounty, in `asset`.
+		pub share: Balance,
+		/// Asset the bounty is posted in, `None` for the native token.
+		pub asset: Option<AssetId>,
+	}
+
+	/// Request of the consumer going by `callback` for the random number of a cycle.
+	#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, Clone, PartialEq, Eq, TypeInfo)]
+	pub struct RandomnessRequest {
+		pub id: RequestId,
+		pub callback: CallbackId,
+	}
+
+	/// Participant of a cycle, as returned by the runtime API.
+	#[derive(RuntimeDebug, Encode, Decode, Clone, PartialEq, Eq, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub struct GeneratorDetails<AccountId> {
+		pub account: AccountId,
+		pub revealed: bool,
 	}
 
 	pub(crate) type BalanceOf<T> = <T as Config>::Balance;
 	pub(crate) type AccountIdOf<T> = <T as frame_system::Config>::AccountId;
-	pub(crate) type RngCycleOf<T> = RngCycle<AccountIdOf<T>, BalanceOf<T>, BlockNumberOf<T>, u64>;
+	pub(crate) type RngCycleOf<T> = RngCycle<AccountIdOf<T>, BalanceOf<T>, BlockNumberOf<T>, H256>;
+	/// Entropies of the secrets revealed in a cycle, there are at most `u8::MAX` generators.
+	pub(crate) type EntropiesOf = BoundedVec<H256, ConstU32<{ u8::MAX as u32 }>>;
 	pub(crate) type BlockNumberOf<T> = <T as frame_system::Config>::BlockNumber;
+	pub(crate) type CycleParametersOf<T> = CycleParameters<BalanceOf<T>, BlockNumberOf<T>>;
+	pub(crate) type ParametersOf<T> = Parameters<BalanceOf<T>, BlockNumberOf<T>>;
+	pub(crate) type RandomnessRecordOf<T> =
+		RandomnessRecord<<T as Config>::CycleId, BlockNumberOf<T>>;
+	pub(crate) type PredictedPayoutOf<T> = PredictedPayout<<T as Config>::AssetId, BalanceOf<T>>;
+
+	/// The current storage version.
+	pub(crate) const STORAGE_VERSION: StorageVersion = StorageVersion::new(6);
+
+	/// Most cycles the off-chain worker commits to in a block, so that it does not lock its
+	/// deposits in all cycles at once.
+	pub(crate) const MAX_OFFCHAIN_COMMITS: u32 = 4;
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
-	pub trait Config: frame_system::Config {
+	pub trait Config: CreateSignedTransaction<Call<Self>> + frame_system::Config {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
@@ -72,6 +311,7 @@ pub mod pallet {
 			+ Default
 			+ Debug
 			+ TypeInfo
+			+ MaybeSerializeDeserialize
 			+ CheckedAdd
 			+ One
 			+ Sized
@@ -79,12 +319,14 @@ pub mod pallet {
 			+ Clone
 			+ Copy
 			+ Eq
-			+ PartialEq;
+			+ PartialEq
+			+ PartialOrd;
 		type Balance: FullCodec
 			+ MaxEncodedLen
 			+ Default
 			+ Debug
 			+ TypeInfo
+			+ MaybeSerializeDeserialize
 			+ AtLeast32BitUnsigned
 			+ CheckedAdd
 			+ CheckedSub
@@ -92,28 +334,136 @@ pub mod pallet {
 
 		type Balances: Transfer<AccountIdOf<Self>, Balance = BalanceOf<Self>>;
 
+		/// Where bounties and deposits are kept while a cycle runs, see [`crate::funds`].
+		type Funds: CycleFunds<Self>;
+
+		/// Identifier of the assets a bounty can be posted in instead of the native token.
+		type AssetId: Member + Parameter + MaxEncodedLen + Copy;
+
+		/// Assets a bounty can be posted in. Bounties in an asset are kept in the cycle's account,
+		/// deposits are always paid with `Funds`.
+		type Assets: fungibles::Transfer<
+			AccountIdOf<Self>,
+			AssetId = Self::AssetId,
+			Balance = BalanceOf<Self>,
+		>;
+
+		/// Origin allowed to choose the assets bounties can be posted in.
+		type AssetOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Minimum native bounty, unless overridden with `set_parameters`.
 		#[pallet::constant]
 		type MinBounty: Get<BalanceOf<Self>>;
 
+		/// Default deposit of a generator, used when cycle is created without parameters. This and
+		/// the other defaults of cycle parameters can be overridden with `set_parameters`.
 		#[pallet::constant]
 		type Deposit: Get<BalanceOf<Self>>;
 
+		/// Default delay before bots can participate.
 		#[pallet::constant]
 		type DelayBeforeBots: Get<BlockNumberOf<Self>>;
 
+		/// Default delay between bots joining and the start of second phase.
 		#[pallet::constant]
 		type DelayBeforeSecondPhase: Get<BlockNumberOf<Self>>;
 
+		/// Default duration of second phase.
 		#[pallet::constant]
 		type SecondPhaseDuration: Get<BlockNumberOf<Self>>;
 
+		/// Default maximum number of generators.
 		#[pallet::constant]
 		type MaxGenerators: Get<u8>;
 
+		/// Default number of revealed secrets needed for a cycle to complete. A single revealer
+		/// would choose the random number alone.
+		#[pallet::constant]
+		type MinRevealers: Get<u8>;
+
+		/// Blocks the commit phase of a cycle is extended by when it would end with fewer
+		/// commitments than the cycle needs reveals. Zero disables extensions.
+		#[pallet::constant]
+		type CommitPhaseExtension: Get<BlockNumberOf<Self>>;
+
+		/// Maximum number of times the commit phase of a cycle is extended.
+		#[pallet::constant]
+		type MaxCommitPhaseExtensions: Get<u8>;
+
+		/// Lower bounds of the parameters a creator can choose for a cycle.
+		#[pallet::constant]
+		type MinCycleParameters: Get<CycleParametersOf<Self>>;
+
+		/// Upper bounds of the parameters a creator can choose for a cycle.
+		#[pallet::constant]
+		type MaxCycleParameters: Get<CycleParametersOf<Self>>;
+
 		#[pallet::constant]
 		type PalletId: Get<PalletId>;
 
-		type WeightInfo: RngDaoWeightInfo;
+		/// Account receiving forfeited deposits and whatever is left in a cycle's account once
+		/// the cycle is finalised, e.g. a treasury pallet account.
+		#[pallet::constant]
+		type TreasuryAccount: Get<AccountIdOf<Self>>;
+
+		/// Maximum number of cycles which can reach their deadline in the same block. Bounds the
+		/// work done by `on_initialize` to finalise them.
+		#[pallet::constant]
+		type MaxCyclesPerBlock: Get<u32>;
+
+		/// Times `on_initialize` tries again to finalise a cycle which failed to finalise at its
+		/// deadline. After that only `get_random_number` of its creator finalises it.
+		#[pallet::constant]
+		type MaxFinalisationRetries: Get<u32>;
+
+		/// Combines entropies of the revealed secrets into the random number of a cycle.
+		type Combiner: Combiner;
+
+		/// Number of random numbers of the latest completed cycles kept in `RandomnessHistory`.
+		#[pallet::constant]
+		type MaxHistory: Get<u32>;
+
+		/// Blocks a finalised cycle is kept for after its deadline, before `reap_cycle` can remove
+		/// it.
+		#[pallet::constant]
+		type RetentionPeriod: Get<BlockNumberOf<Self>>;
+
+		/// Pallets called with the random numbers they requested, see [`crate::consumer`].
+		type Consumers: RandomnessConsumer;
+
+		/// Maximum number of requests waiting for their cycle to complete or for their consumer to
+		/// be called.
+		#[pallet::constant]
+		type MaxPendingRequests: Get<u32>;
+
+		/// Maximum number of pending requests of a single consumer, so that one consumer can not
+		/// take up all of `MaxPendingRequests`.
+		#[pallet::constant]
+		type MaxRequestsPerConsumer: Get<u32>;
+
+		/// Weight consumers can use in a block. Consumers not fitting in it are called in the next
+		/// blocks.
+		#[pallet::constant]
+		type MaxCallbackWeight: Get<Weight>;
+
+		/// Maximum number of accounts other than its creator topping up the bounty of a cycle.
+		#[pallet::constant]
+		type MaxContributors: Get<u32>;
+
+		/// Mitigation of generators withholding their secret, see [`crate::withholding`]. Bounties
+		/// in assets are left out, as deposits are paid in the native token.
+		type Withholding: Withholding<BalanceOf<Self>>;
+
+		/// Origin allowed to manage the bot registry and the share of bounty paid to bots.
+		type BotOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Origin allowed to override the minimum bounty and default cycle parameters.
+		type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Keys the off-chain worker takes part in cycles with, see [`crate::crypto`].
+		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+
+		type WeightInfo: WeightInfo;
 	}
 
 	#[pallet::type_value]
@@ -140,14 +490,188 @@ pub mod pallet {
 		Generator,
 	>;
 
+	/// Secrets revealed in a cycle. Generators are moved here from `Generators` on reveal.
+	#[pallet::storage]
+	#[pallet::getter(fn reveals)]
+	pub type Reveals<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, T::CycleId, Blake2_128Concat, AccountIdOf<T>, Secret>;
+
+	/// Operator accounts registered as bots. Bots commit only after `delay_before_bots` and are
+	/// paid according to `BotSharePolicy`.
+	#[pallet::storage]
+	pub type Bots<T: Config> = StorageMap<_, Blake2_128Concat, AccountIdOf<T>, ()>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn bot_share_policy)]
+	pub type BotSharePolicy<T: Config> = StorageValue<_, BotShare<BalanceOf<T>>, ValueQuery>;
+
+	/// Entropies of the secrets revealed so far in a running cycle, in reveal order.
+	#[pallet::storage]
+	#[pallet::getter(fn revealed_entropies)]
+	pub type RevealedEntropies<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::CycleId, EntropiesOf, ValueQuery>;
+
+	/// Cycles to be finalised by `on_initialize`, indexed by their deadline block.
+	#[pallet::storage]
+	#[pallet::getter(fn cycle_deadlines)]
+	pub type CycleDeadlines<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		BlockNumberOf<T>,
+		BoundedVec<T::CycleId, T::MaxCyclesPerBlock>,
+		ValueQuery,
+	>;
+
+	/// Times `on_initialize` failed to finalise a cycle it still tries to finalise.
+	#[pallet::storage]
+	#[pallet::getter(fn finalisation_retries)]
+	pub type FinalisationRetries<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::CycleId, u32, ValueQuery>;
+
+	/// Minimum bounty of cycles funded in an asset. Bounties can only be posted in assets listed
+	/// here.
+	#[pallet::storage]
+	#[pallet::getter(fn min_asset_bounty)]
+	pub type MinAssetBounties<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AssetId, BalanceOf<T>>;
+
+	/// Asset the bounty of a cycle is paid in, for cycles not funded in the native token.
+	#[pallet::storage]
+	#[pallet::getter(fn bounty_asset)]
+	pub type BountyAssets<T: Config> = StorageMap<_, Blake2_128Concat, T::CycleId, T::AssetId>;
+
+	/// Top-ups of the bounty of a running cycle by accounts other than its creator, along with the
+	/// total each of them added. They are refunded if the cycle is cancelled or fails.
+	#[pallet::storage]
+	#[pallet::getter(fn bounty_contributions)]
+	pub type BountyContributions<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::CycleId,
+		BoundedVec<(AccountIdOf<T>, BalanceOf<T>), T::MaxContributors>,
+		ValueQuery,
+	>;
+
+	/// Shares of generators who revealed before the cycle had the reveals it needs to complete,
+	/// paid once it has them.
+	#[pallet::storage]
+	#[pallet::getter(fn unpaid_shares)]
+	pub type UnpaidShares<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::CycleId,
+		Blake2_128Concat,
+		AccountIdOf<T>,
+		BalanceOf<T>,
+	>;
+
+	/// Random number of the latest completed cycle and the block in which it became known.
+	#[pallet::storage]
+	#[pallet::getter(fn latest_random_number)]
+	pub type LatestRandomNumber<T: Config> = StorageValue<_, (H256, BlockNumberOf<T>)>;
+
+	/// Random numbers of the latest `MaxHistory` completed cycles, oldest first. The oldest one is
+	/// dropped to make room for a new one, they outlive the cycles removed by `reap_cycle`.
+	#[pallet::storage]
+	#[pallet::getter(fn randomness_history)]
+	pub type RandomnessHistory<T: Config> =
+		StorageValue<_, BoundedVec<RandomnessRecordOf<T>, T::MaxHistory>, ValueQuery>;
+
+	/// Id of the next request for randomness.
+	#[pallet::storage]
+	#[pallet::getter(fn next_request_id)]
+	pub type NextRequestId<T: Config> = StorageValue<_, RequestId, ValueQuery>;
+
+	/// Requests waiting for a cycle to complete, in the order they were made.
+	#[pallet::storage]
+	#[pallet::getter(fn randomness_requests)]
+	pub type RandomnessRequests<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::CycleId,
+		BoundedVec<RandomnessRequest, T::MaxPendingRequests>,
+		ValueQuery,
+	>;
+
+	/// Requests of completed cycles along with their random number, oldest first. Their consumers
+	/// are called by `on_initialize` as long as they fit in `MaxCallbackWeight`.
+	#[pallet::storage]
+	#[pallet::getter(fn queued_callbacks)]
+	pub type QueuedCallbacks<T: Config> =
+		StorageValue<_, BoundedVec<(RandomnessRequest, H256), T::MaxPendingRequests>, ValueQuery>;
+
+	/// Number of requests in `RandomnessRequests` and `QueuedCallbacks`, at most
+	/// `MaxPendingRequests`.
+	#[pallet::storage]
+	#[pallet::getter(fn pending_requests)]
+	pub type PendingRequests<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// Number of pending requests of every consumer with any, at most `MaxRequestsPerConsumer`.
+	#[pallet::storage]
+	#[pallet::getter(fn consumer_requests)]
+	pub type ConsumerRequests<T: Config> =
+		StorageMap<_, Blake2_128Concat, CallbackId, u32, ValueQuery>;
+
+	/// Parameters set by `AdminOrigin`, the runtime's constants are used while there are none.
+	#[pallet::storage]
+	#[pallet::getter(fn pallet_parameters)]
+	pub type PalletParameters<T: Config> = StorageValue<_, ParametersOf<T>>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Id of the first cycle.
+		pub cycle_count: T::CycleId,
+		/// Cycles started in the genesis block by their creators, with native bounties and their
+		/// own parameters or the default ones.
+		pub cycles: Vec<(AccountIdOf<T>, BalanceOf<T>, Option<CycleParametersOf<T>>)>,
+		/// Bot operator accounts.
+		pub bots: Vec<AccountIdOf<T>>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { cycle_count: Zero::zero(), cycles: Vec::new(), bots: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			CycleCount::<T>::put(self.cycle_count);
+			for (creator, bounty, params) in &self.cycles {
+				Pallet::<T>::start_cycle(creator, bounty.clone(), params.clone(), None)
+					.expect("cycles of the genesis config are valid and funded");
+			}
+			for bot in &self.bots {
+				Bots::<T>::insert(bot, ());
+			}
+		}
+	}
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
 		CycleCreated { bounty: T::Balance, creator: T::AccountId },
 		HashReceived { cycle_id: T::CycleId, sender: T::AccountId, hash: H256 },
 		SecretReceived { cycle_id: T::CycleId, sender: T::AccountId },
-		CycleCompleted { cycle_id: T::CycleId, creator: T::AccountId, random_number: u64 },
+		CycleCompleted { cycle_id: T::CycleId, creator: T::AccountId, random_number: H256 },
 		CycleFailed { cycle_id: T::CycleId, creator: T::AccountId },
+		CycleSettled { cycle_id: T::CycleId, slashed: T::Balance, leftover: T::Balance },
+		BotRegistered { bot: T::AccountId },
+		BotDeregistered { bot: T::AccountId },
+		BotSharePolicySet { policy: BotShare<T::Balance> },
+		MinAssetBountySet { asset: T::AssetId, min_bounty: Option<T::Balance> },
+		CycleCancelled { cycle_id: T::CycleId, creator: T::AccountId },
+		BountyToppedUp { cycle_id: T::CycleId, sender: T::AccountId, amount: T::Balance },
+		ParametersUpdated { parameters: Option<ParametersOf<T>> },
+		CycleReaped { cycle_id: T::CycleId },
+		CycleFinalisationFailed { cycle_id: T::CycleId, error: DispatchError },
+		CycleFinalisationAbandoned { cycle_id: T::CycleId },
+		RandomnessRequested { request_id: RequestId, cycle_id: T::CycleId, callback: CallbackId },
+		RandomnessDelivered { request_id: RequestId },
+		RandomnessRequestsDropped { cycle_id: T::CycleId },
+		RandomnessRequestDropped { request_id: RequestId },
 	}
 
 	#[pallet::error]
@@ -161,38 +685,173 @@ pub mod pallet {
 		RandomNumberNotYetGenerated,
 		SecretDoesNotMatchHash,
 		NotSubmitedHashInFirstPhase,
+		CycleAlreadyFinalised,
+		CommitPhaseOver,
+		RevealPhaseOver,
+		InvalidCycleParameters,
+		BotAlreadyRegistered,
+		NotABot,
+		AlreadyCommitted,
+		AssetNotAccepted,
+		NotAuthorizedToCancelCycle,
+		CycleAlreadyJoined,
+		InvalidParameters,
+		CycleNotFinalised,
+		RetentionPeriodNotOver,
+		UnknownCallback,
+		CallbackTooHeavy,
+		TooManyRequests,
+		TooManyConsumerRequests,
+		TooManyContributors,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		/// Finalises every cycle whose deadline is `now`, or schedules it again if its commit phase
+		/// was extended meanwhile. A cycle failing to finalise here is retried in the next block,
+		/// at most `MaxFinalisationRetries` times, its creator can still call `get_random_number`
+		/// meanwhile and after that. Then calls consumers of the random numbers of completed
+		/// cycles, those of cycles completed in this block included.
+		fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+			let mut weight = T::DbWeight::get().reads_writes(1, 1);
+			for cycle_id in CycleDeadlines::<T>::take(now) {
+				let mut counts = (0, 0, 0);
+				let result = with_storage_layer(|| -> DispatchResult {
+					Cycles::<T>::try_mutate(cycle_id, |cycle| -> DispatchResult {
+						let cycle = cycle.as_mut().ok_or(Error::<T>::NoCycleFound)?;
+						let (unrevealed, revealed) = Self::generator_counts(cycle);
+						counts = (unrevealed, revealed, Self::contributor_count(&cycle_id));
+						ensure!(!cycle.status.is_finalised(), Error::<T>::CycleAlreadyFinalised);
+						Self::advance(cycle, now);
+						if Self::cycle_deadline(cycle) > now {
+							// commit phase was extended, the cycle is finalised at its new deadline
+							weight = weight.saturating_add(Self::schedule_finalisation(
+								cycle_id,
+								Self::cycle_deadline(cycle),
+							));
+							return Ok(())
+						}
+						ensure!(
+							cycle.status == CycleStatus::Finished,
+							Error::<T>::RandomNumberNotYetGenerated
+						);
+						Self::finalise_cycle(&cycle_id, cycle)
+					})
+				});
+				match result {
+					// finalised by its creator or removed meanwhile
+					Err(error)
+						if error == Error::<T>::CycleAlreadyFinalised.into() ||
+							error == Error::<T>::NoCycleFound.into() => {},
+					Err(error) => {
+						Self::deposit_event(Event::CycleFinalisationFailed { cycle_id, error });
+						let retries = FinalisationRetries::<T>::get(cycle_id);
+						if retries < T::MaxFinalisationRetries::get() {
+							FinalisationRetries::<T>::insert(cycle_id, retries + 1);
+							weight = weight.saturating_add(Self::schedule_finalisation(
+								cycle_id,
+								now.saturating_add(One::one()),
+							));
+						} else {
+							// left to its creator
+							FinalisationRetries::<T>::remove(cycle_id);
+							Self::deposit_event(Event::CycleFinalisationAbandoned { cycle_id });
+						}
+						weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+					},
+					Ok(()) => {},
+				}
+				let (unrevealed, revealed, contributors) = counts;
+				weight = weight
+					.saturating_add(T::WeightInfo::finalise_cycle(unrevealed, revealed))
+					.saturating_add(T::WeightInfo::refund_bounty(contributors));
+			}
+			weight.saturating_add(Self::deliver_randomness(T::MaxCallbackWeight::get()))
+		}
+
+		/// Takes part as a bot in cycles with fewer generators than they need revealers, using a
+		/// `rngd` key of this node. The key's account should be a registered bot, otherwise it
+		/// commits as a human. Secrets are kept in local off-chain storage until they are revealed.
+		fn offchain_worker(now: BlockNumberFor<T>) {
+			let signer = Signer::<T, T::AuthorityId>::any_account();
+			if !signer.can_sign() {
+				return
+			}
+			let mut lock = StorageLock::<Time>::new(b"rng-dao::bot-lock");
+			let _guard = match lock.try_lock() {
+				Ok(guard) => guard,
+				Err(_) => return,
+			};
+			// transactions sent now are included in the next block at the earliest
+			let next = now.saturating_add(One::one());
+			// only cycles waiting for their deadline, finalised ones are not even decoded
+			let mut cycle_ids = Vec::new();
+			let mut cycles = Vec::new();
+			for (deadline, ids) in CycleDeadlines::<T>::iter() {
+				for cycle_id in ids {
+					if cycle_ids.contains(&cycle_id) {
+						continue
+					}
+					cycle_ids.push(cycle_id);
+					if let Some(cycle) = Cycles::<T>::get(cycle_id) {
+						cycles.push((deadline, cycle_id, cycle));
+					}
+				}
+			}
+			// cycles furthest from the generators they need first, then the ones closest to their
+			// deadline
+			cycles.sort_by_key(|(deadline, _, cycle)| {
+				(
+					sp_std::cmp::Reverse(
+						Self::min_revealers(cycle).saturating_sub(cycle.generators_count),
+					),
+					*deadline,
+				)
+			});
+			let mut commits = 0;
+			for (_, cycle_id, cycle) in cycles {
+				let key = Self::bot_commitment_key(&cycle_id);
+				let mut storage = StorageValueRef::persistent(&key);
+				match Self::status_at(&cycle, next) {
+					CycleStatus::BotCommitPhase
+						if cycle.generators_count < Self::min_revealers(&cycle) &&
+							commits < MAX_OFFCHAIN_COMMITS =>
+						if Self::offchain_commit(&signer, cycle_id, storage) {
+							commits += 1;
+						},
+					CycleStatus::RevealPhase => Self::offchain_reveal(cycle_id, storage),
+					CycleStatus::Finished |
+					CycleStatus::Completed |
+					CycleStatus::Failed |
+					CycleStatus::Cancelled => storage.clear(),
+					_ => {},
+				}
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn try_state(_n: BlockNumberFor<T>) -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
+		/// Creates a cycle whose generators share `bounty`, paid in `asset` or in the native token
+		/// if it is `None`.
 		#[pallet::call_index(0)]
-		#[pallet::weight(T::WeightInfo::create_new_rng_cycle())]
-		pub fn create_new_rng_cycle(origin: OriginFor<T>, bounty: BalanceOf<T>) -> DispatchResult {
+		#[pallet::weight(match asset {
+			Some(_) => T::WeightInfo::create_new_rng_cycle_with_asset(),
+			None => T::WeightInfo::create_new_rng_cycle(),
+		})]
+		pub fn create_new_rng_cycle(
+			origin: OriginFor<T>,
+			bounty: BalanceOf<T>,
+			params: Option<CycleParametersOf<T>>,
+			asset: Option<T::AssetId>,
+		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
-			ensure!(bounty >= T::MinBounty::get(), Error::<T>::BountyMustBeGreaterThanMinBounty);
-			let cycle_id =
-				CycleCount::<T>::try_mutate(|cycle_count| -> Result<T::CycleId, DispatchError> {
-					let cycle_id = *cycle_count;
-
-					Cycles::<T>::insert(
-						cycle_id.clone(),
-						RngCycleOf::<T> {
-							creator: who.clone(),
-							bounty: bounty.clone(),
-							started: <frame_system::Pallet<T>>::block_number(),
-							random_number: 0_u64,
-							generators_count: 0_u8,
-							revealed_count: 0_u8,
-						},
-					);
-					*cycle_count = cycle_id
-						.checked_add(&T::CycleId::one())
-						.ok_or(ArithmeticError::Overflow)?;
-					Ok(cycle_id)
-				})?;
-			T::Balances::transfer(&who, &Self::account_id(&cycle_id), bounty.clone(), true)?;
-
-			Self::deposit_event(Event::CycleCreated { bounty, creator: who });
+			Self::start_cycle(&who, bounty, params, asset)?;
 			Ok(())
 		}
 
@@ -202,33 +861,41 @@ pub mod pallet {
 			origin: OriginFor<T>,
 			cycle_id: T::CycleId,
 			hash: sp_core::H256,
-			is_bot: bool,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			let is_bot = Self::is_bot(&who);
 			Cycles::<T>::try_mutate(cycle_id, |cycle| -> DispatchResult {
-				let mut cycle = cycle.as_mut().ok_or(Error::<T>::NoCycleFound)?;
+				let cycle = cycle.as_mut().ok_or(Error::<T>::NoCycleFound)?;
+				let now = <frame_system::Pallet<T>>::block_number();
+				Self::advance(cycle, now);
+				ensure!(!cycle.status.is_finalised(), Error::<T>::CycleAlreadyFinalised);
+				// hashes are accepted only until second phase starts
+				ensure!(
+					matches!(cycle.status, CycleStatus::CommitPhase | CycleStatus::BotCommitPhase),
+					Error::<T>::CommitPhaseOver
+				);
+				// one commitment per account, a second one would take another deposit and count
+				// as another generator
+				ensure!(
+					!Generators::<T>::contains_key(cycle_id, &who),
+					Error::<T>::AlreadyCommitted
+				);
 				// check for MaxGeneratorsReached error
-				let max_genrators: u8 = T::MaxGenerators::get();
+				let max_genrators: u8 = cycle.params.max_generators;
 				ensure!(
 					cycle.generators_count + 1 <= max_genrators,
 					Error::<T>::MaxGeneratorsReached
 				);
 				cycle.generators_count += 1;
-				let now = <frame_system::Pallet<T>>::block_number();
 				// bots can participate only after some delay
 				ensure!(
-					!is_bot || now > cycle.started + T::DelayBeforeBots::get(),
+					!is_bot || cycle.status != CycleStatus::CommitPhase,
 					Error::<T>::BotsNotAllowedYet
 				);
-				let generator = Generator { secret: 0_u64, hash, is_bot };
+				let generator = Generator { hash, is_bot, version: CommitmentVersion::Salted };
 				Generators::<T>::insert(cycle_id, who.clone(), generator);
 
-				T::Balances::transfer(
-					&who,
-					&Self::account_id(&cycle_id.clone()),
-					T::Deposit::get(),
-					true,
-				)?;
+				T::Funds::hold(&cycle_id, &who, cycle.params.deposit.clone())?;
 				Ok(())
 			})?;
 
@@ -238,97 +905,1037 @@ pub mod pallet {
 
 		/// As soon as generator reveals correct secret, his/her deposit + reward is returned.
 		/// If secret is different than hash commited in first phase then he/she looses deposit.
+		/// See [`crate::commitment`] for how the hash is computed from `secret` and `salt`.
 		#[pallet::call_index(2)]
-		#[pallet::weight(T::WeightInfo::reveal_secret())]
+		#[pallet::weight(T::WeightInfo::reveal_secret(
+			T::MaxCycleParameters::get().max_generators.into()
+		))]
 		pub fn reveal_secret(
 			origin: OriginFor<T>,
 			cycle_id: T::CycleId,
-			secret: u64,
-			_is_bot: bool,
-		) -> DispatchResult {
+			secret: Secret,
+			salt: Salt,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let now = <frame_system::Pallet<T>>::block_number();
+			let revealed =
+				Cycles::<T>::try_mutate(cycle_id, |cycle| -> Result<u8, DispatchError> {
+					let cycle = cycle.as_mut().ok_or(Error::<T>::NoCycleFound)?;
+					Self::advance(cycle, now);
+					ensure!(!cycle.status.is_finalised(), Error::<T>::CycleAlreadyFinalised);
+					ensure!(
+						!matches!(
+							cycle.status,
+							CycleStatus::CommitPhase | CycleStatus::BotCommitPhase
+						),
+						Error::<T>::SecondPhaseNotStartedYet
+					);
+					ensure!(cycle.status == CycleStatus::RevealPhase, Error::<T>::RevealPhaseOver);
+					let generator = Generators::<T>::get(cycle_id, who.clone())
+						.ok_or(Error::<T>::NotSubmitedHashInFirstPhase)?;
+					// compute hash and see if they matches
+					let hash = generator.version.commitment(&cycle_id, &who, &salt, &secret);
+					if hash == generator.hash {
+						let revealed = cycle.revealed_count;
+						// reward the generator and increment revealed_count
+						// keep entropy for the random_number
+						cycle.revealed_count += 1;
+						RevealedEntropies::<T>::try_append(
+							cycle_id,
+							generator.version.entropy(&secret),
+						)
+						.map_err(|_| Error::<T>::MaxGeneratorsReached)?;
+						let share = Self::generator_share(cycle, generator.is_bot)?;
+						T::Funds::pay(&cycle_id, &who, &who, cycle.params.deposit.clone())?;
+						if cycle.revealed_count < Self::min_revealers(cycle) {
+							// paid only if the cycle completes
+							UnpaidShares::<T>::insert(cycle_id, &who, share);
+						} else {
+							for (revealer, share) in UnpaidShares::<T>::drain_prefix(cycle_id) {
+								Self::pay_bounty(&cycle_id, cycle, &revealer, share)?;
+							}
+							Self::pay_bounty(&cycle_id, cycle, &who, share)?;
+						}
+						Self::deposit_event(Event::SecretReceived {
+							cycle_id,
+							sender: who.clone(),
+						});
+						Ok(revealed)
+					} else {
+						return Err(Error::<T>::SecretDoesNotMatchHash.into())
+					}
+				})?;
+			// remove generator from storage
+			Generators::<T>::remove(cycle_id, who.clone());
+			Reveals::<T>::insert(cycle_id, who, secret);
+			Ok(Some(T::WeightInfo::reveal_secret(revealed.into())).into())
+		}
+
+		#[pallet::call_index(3)]
+		#[pallet::weight({
+			// worst case of both, unrevealed generators and reveals, and of contributors
+			let max_generators = T::MaxCycleParameters::get().max_generators.into();
+			T::WeightInfo::get_random_number(max_generators, max_generators)
+				.saturating_add(T::WeightInfo::refund_bounty(T::MaxContributors::get()))
+		})]
+		pub fn get_random_number(
+			origin: OriginFor<T>,
+			cycle_id: T::CycleId,
+		) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
 			let now = <frame_system::Pallet<T>>::block_number();
+			let contributors = Self::contributor_count(&cycle_id);
+			let (unrevealed, revealed) =
+				Cycles::<T>::try_mutate(cycle_id, |cycle| -> Result<(u32, u32), DispatchError> {
+					let cycle = cycle.as_mut().ok_or(Error::<T>::NoCycleFound)?;
+					// only creator can execute this extrinsic
+					ensure!(cycle.creator == who, Error::<T>::NotAuthorizedToGetRandomNumber);
+					// a cycle is finalised only once
+					ensure!(!cycle.status.is_finalised(), Error::<T>::CycleAlreadyFinalised);
+					Self::advance(cycle, now);
+					ensure!(
+						cycle.status == CycleStatus::Finished,
+						Error::<T>::RandomNumberNotYetGenerated
+					);
+					Self::finalise_cycle(&cycle_id, cycle)?;
+					Ok(Self::generator_counts(cycle))
+				})?;
+			Ok(Some(
+				T::WeightInfo::get_random_number(unrevealed, revealed)
+					.saturating_add(T::WeightInfo::refund_bounty(contributors)),
+			)
+			.into())
+		}
+
+		#[pallet::call_index(4)]
+		#[pallet::weight(T::WeightInfo::register_bot())]
+		pub fn register_bot(origin: OriginFor<T>, bot: AccountIdOf<T>) -> DispatchResult {
+			T::BotOrigin::ensure_origin(origin)?;
+			ensure!(!Self::is_bot(&bot), Error::<T>::BotAlreadyRegistered);
+			Bots::<T>::insert(&bot, ());
+			Self::deposit_event(Event::BotRegistered { bot });
+			Ok(())
+		}
+
+		/// Bot's commitments made before deregistration are still paid as the ones of a bot.
+		#[pallet::call_index(5)]
+		#[pallet::weight(T::WeightInfo::deregister_bot())]
+		pub fn deregister_bot(origin: OriginFor<T>, bot: AccountIdOf<T>) -> DispatchResult {
+			T::BotOrigin::ensure_origin(origin)?;
+			ensure!(Self::is_bot(&bot), Error::<T>::NotABot);
+			Bots::<T>::remove(&bot);
+			Self::deposit_event(Event::BotDeregistered { bot });
+			Ok(())
+		}
+
+		/// Applies to every reveal of a bot from now on, including the ones in running cycles.
+		#[pallet::call_index(6)]
+		#[pallet::weight(T::WeightInfo::set_bot_share_policy())]
+		pub fn set_bot_share_policy(
+			origin: OriginFor<T>,
+			policy: BotShare<BalanceOf<T>>,
+		) -> DispatchResult {
+			T::BotOrigin::ensure_origin(origin)?;
+			BotSharePolicy::<T>::put(policy.clone());
+			Self::deposit_event(Event::BotSharePolicySet { policy });
+			Ok(())
+		}
+
+		/// Accepts bounties in `asset` of at least `min_bounty`, or stops accepting them if it is
+		/// `None`. Cycles already funded in `asset` are not affected.
+		#[pallet::call_index(7)]
+		#[pallet::weight(T::WeightInfo::set_min_asset_bounty())]
+		pub fn set_min_asset_bounty(
+			origin: OriginFor<T>,
+			asset: T::AssetId,
+			min_bounty: Option<BalanceOf<T>>,
+		) -> DispatchResult {
+			T::AssetOrigin::ensure_origin(origin)?;
+			MinAssetBounties::<T>::set(asset, min_bounty.clone());
+			Self::deposit_event(Event::MinAssetBountySet { asset, min_bounty });
+			Ok(())
+		}
+
+		/// Cancels a cycle nobody committed to and returns its bounty to the creator, top-ups to
+		/// those who contributed them.
+		#[pallet::call_index(8)]
+		#[pallet::weight(
+			T::WeightInfo::cancel_cycle()
+				.saturating_add(T::WeightInfo::refund_bounty(T::MaxContributors::get()))
+		)]
+		pub fn cancel_cycle(
+			origin: OriginFor<T>,
+			cycle_id: T::CycleId,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let contributors = Self::contributor_count(&cycle_id);
 			Cycles::<T>::try_mutate(cycle_id, |cycle| -> DispatchResult {
-				let mut cycle = cycle.as_mut().ok_or(Error::<T>::NoCycleFound)?;
-				let second_phase_start =
-					cycle.started + T::DelayBeforeBots::get() + T::DelayBeforeSecondPhase::get();
-				ensure!(now >= second_phase_start, Error::<T>::SecondPhaseNotStartedYet);
-				let generator = Generators::<T>::get(cycle_id, who.clone())
-					.ok_or(Error::<T>::NotSubmitedHashInFirstPhase)?;
-				// compute hash and see if they matches
-				let bytes = secret.to_le_bytes();
-				let hash = Keccak256::hash(&bytes);
-				if hash == generator.hash {
-					// reward the generator and increment revealed_count
-					// update random_number
-					cycle.revealed_count += 1;
-					cycle.random_number ^= secret;
-					let total_shares = cycle.generators_count + 1; // add one for our profit share
-					let share = cycle
-						.bounty
-						.checked_div(&total_shares.saturated_into())
-						.ok_or(ArithmeticError::Underflow)?;
-					let transfer_value = share
-						.checked_add(&T::Deposit::get().saturated_into())
-						.ok_or(ArithmeticError::Overflow)?;
+				let cycle = cycle.as_mut().ok_or(Error::<T>::NoCycleFound)?;
+				ensure!(cycle.creator == who, Error::<T>::NotAuthorizedToCancelCycle);
+				ensure!(!cycle.status.is_finalised(), Error::<T>::CycleAlreadyFinalised);
+				ensure!(cycle.generators_count == 0, Error::<T>::CycleAlreadyJoined);
+				Self::refund_bounty(&cycle_id, cycle)?;
+				Self::settle_cycle(&cycle_id, cycle)?;
+				Self::drop_requests(&cycle_id);
+				FinalisationRetries::<T>::remove(cycle_id);
+				cycle.status = CycleStatus::Cancelled;
+				// nothing left to finalise at the deadline, a cycle scheduled after its deadline
+				// for lack of room is skipped there as it is finalised already
+				CycleDeadlines::<T>::mutate(Self::cycle_deadline(cycle), |cycle_ids| {
+					cycle_ids.retain(|id| *id != cycle_id)
+				});
+				Ok(())
+			})?;
+			Self::deposit_event(Event::CycleCancelled { cycle_id, creator: who });
+			Ok(Some(
+				T::WeightInfo::cancel_cycle()
+					.saturating_add(T::WeightInfo::refund_bounty(contributors)),
+			)
+			.into())
+		}
 
-					T::Balances::transfer(
-						&Self::account_id(&cycle_id.clone()),
-						&who,
-						transfer_value,
-						true,
-					)?;
-					Self::deposit_event(Event::SecretReceived { cycle_id, sender: who.clone() });
-					Ok(())
-				} else {
-					return Err(Error::<T>::SecretDoesNotMatchHash.into())
+		/// Adds `amount` to the bounty of a cycle in its commit phase, in the bounty's asset if it
+		/// has one. Top-ups become part of the bounty and are returned to those who contributed
+		/// them if the cycle is cancelled or fails.
+		#[pallet::call_index(9)]
+		#[pallet::weight(T::WeightInfo::top_up_bounty())]
+		pub fn top_up_bounty(
+			origin: OriginFor<T>,
+			cycle_id: T::CycleId,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let now = <frame_system::Pallet<T>>::block_number();
+			Cycles::<T>::try_mutate(cycle_id, |cycle| -> DispatchResult {
+				let cycle = cycle.as_mut().ok_or(Error::<T>::NoCycleFound)?;
+				Self::advance(cycle, now);
+				ensure!(!cycle.status.is_finalised(), Error::<T>::CycleAlreadyFinalised);
+				// shares are paid out of the bounty as secrets are revealed, it must not
+				// change once the first one may have been paid
+				ensure!(
+					matches!(cycle.status, CycleStatus::CommitPhase | CycleStatus::BotCommitPhase),
+					Error::<T>::CommitPhaseOver
+				);
+				cycle.bounty =
+					cycle.bounty.checked_add(&amount).ok_or(ArithmeticError::Overflow)?;
+				// deposits are fixed once the first generator paid it
+				if cycle.generators_count == 0 && !BountyAssets::<T>::contains_key(cycle_id) {
+					cycle.params.deposit =
+						Self::withholding_deposit(cycle.params.deposit.clone(), &cycle.bounty);
+				}
+				if who != cycle.creator {
+					Self::record_contribution(&cycle_id, &who, amount.clone())?;
 				}
+				Self::hold_bounty(&cycle_id, &cycle.creator, &who, amount.clone())?;
+				Ok(())
 			})?;
-			// remove generator from storage
-			Generators::<T>::remove(cycle_id, who);
+			Self::deposit_event(Event::BountyToppedUp { cycle_id, sender: who, amount });
 			Ok(())
 		}
 
-		#[pallet::call_index(3)]
-		#[pallet::weight(T::WeightInfo::get_random_number())]
-		pub fn get_random_number(origin: OriginFor<T>, cycle_id: T::CycleId) -> DispatchResult {
-			let who = ensure_signed(origin)?;
-			// only creator can execute this extrinsic
+		/// Overrides `MinBounty` and the default cycle parameters with `parameters`, or goes back
+		/// to the runtime's constants if it is `None`. Cycles already created keep their
+		/// parameters.
+		#[pallet::call_index(10)]
+		#[pallet::weight(T::WeightInfo::set_parameters())]
+		pub fn set_parameters(
+			origin: OriginFor<T>,
+			parameters: Option<ParametersOf<T>>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			if let Some(parameters) = &parameters {
+				ensure!(
+					parameters
+						.is_valid(&T::MinCycleParameters::get(), &T::MaxCycleParameters::get()),
+					Error::<T>::InvalidParameters
+				);
+			}
+			PalletParameters::<T>::set(parameters.clone());
+			Self::deposit_event(Event::ParametersUpdated { parameters });
+			Ok(())
+		}
+
+		/// Removes a finalised cycle once `RetentionPeriod` blocks have passed since its deadline,
+		/// along with the commitments and secrets of its generators. Anyone can reap a cycle.
+		/// Creators pay no storage deposit to be refunded here, instead the call is free of fees
+		/// when it succeeds. Random number of a reaped cycle stays in `RandomnessHistory` as long
+		/// as it is among the latest ones.
+		#[pallet::call_index(11)]
+		#[pallet::weight({
+			// worst case of both, unrevealed generators and reveals
+			let max_generators = T::MaxCycleParameters::get().max_generators.into();
+			T::WeightInfo::reap_cycle(max_generators, max_generators)
+		})]
+		pub fn reap_cycle(
+			origin: OriginFor<T>,
+			cycle_id: T::CycleId,
+		) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
 			let cycle = Cycles::<T>::get(cycle_id).ok_or(Error::<T>::NoCycleFound)?;
-			ensure!(cycle.creator == who, Error::<T>::NotAuthorizedToGetRandomNumber);
+			ensure!(cycle.status.is_finalised(), Error::<T>::CycleNotFinalised);
 			let now = <frame_system::Pallet<T>>::block_number();
-			let second_phase_start =
-				cycle.started + T::DelayBeforeBots::get() + T::DelayBeforeSecondPhase::get();
-			let finish = second_phase_start + T::SecondPhaseDuration::get();
-			ensure!(now >= finish, Error::<T>::RandomNumberNotYetGenerated);
-			if cycle.generators_count == 0 || cycle.revealed_count == 0 {
+			ensure!(
+				now >= Self::cycle_deadline(&cycle).saturating_add(T::RetentionPeriod::get()),
+				Error::<T>::RetentionPeriodNotOver
+			);
+			Cycles::<T>::remove(cycle_id);
+			// generators who did not reveal lost their deposit already
+			let _ = Generators::<T>::clear_prefix(cycle_id, u8::MAX.into(), None);
+			let _ = Reveals::<T>::clear_prefix(cycle_id, u8::MAX.into(), None);
+			BountyAssets::<T>::remove(cycle_id);
+			BountyContributions::<T>::remove(cycle_id);
+			Self::deposit_event(Event::CycleReaped { cycle_id });
+			let (unrevealed, revealed) = Self::generator_counts(&cycle);
+			Ok((Some(T::WeightInfo::reap_cycle(unrevealed, revealed)), Pays::No).into())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Starts a cycle created by `who`, see `create_new_rng_cycle`.
+		pub(crate) fn start_cycle(
+			who: &AccountIdOf<T>,
+			bounty: BalanceOf<T>,
+			params: Option<CycleParametersOf<T>>,
+			asset: Option<T::AssetId>,
+		) -> Result<T::CycleId, DispatchError> {
+			let min_bounty = match asset {
+				Some(asset) =>
+					MinAssetBounties::<T>::get(asset).ok_or(Error::<T>::AssetNotAccepted)?,
+				None => Self::min_bounty(),
+			};
+			ensure!(bounty >= min_bounty, Error::<T>::BountyMustBeGreaterThanMinBounty);
+			let mut params = match params {
+				Some(params) => {
+					ensure!(
+						params.is_within(
+							&T::MinCycleParameters::get(),
+							&T::MaxCycleParameters::get()
+						) && params.min_revealers <= params.max_generators,
+						Error::<T>::InvalidCycleParameters
+					);
+					params
+				},
+				None => Self::default_cycle_parameters(),
+			};
+			if asset.is_none() {
+				params.deposit = Self::withholding_deposit(params.deposit, &bounty);
+			}
+			let cycle_id =
+				CycleCount::<T>::try_mutate(|cycle_count| -> Result<T::CycleId, DispatchError> {
+					let cycle_id = *cycle_count;
+
+					let cycle = RngCycleOf::<T> {
+						creator: who.clone(),
+						bounty: bounty.clone(),
+						started: <frame_system::Pallet<T>>::block_number(),
+						random_number: H256::zero(),
+						generators_count: 0_u8,
+						revealed_count: 0_u8,
+						status: CycleStatus::CommitPhase,
+						params: params.clone(),
+						commit_extensions: 0,
+					};
+					// schedule automatic finalisation, later if the deadline block is full so that
+					// nobody can keep cycles from being created by filling it
+					Self::schedule_finalisation(cycle_id, Self::cycle_deadline(&cycle));
+					Cycles::<T>::insert(cycle_id.clone(), cycle);
+					if let Some(asset) = asset {
+						BountyAssets::<T>::insert(cycle_id.clone(), asset);
+					}
+					*cycle_count = cycle_id
+						.checked_add(&T::CycleId::one())
+						.ok_or(ArithmeticError::Overflow)?;
+					Ok(cycle_id)
+				})?;
+			if asset.is_some() {
+				// only existing accounts can hold assets which are not sufficient, the cycle's
+				// account is provided for until its bounty is swept
+				frame_system::Pallet::<T>::inc_providers(&Self::account_id(&cycle_id));
+			}
+			Self::hold_bounty(&cycle_id, who, who, bounty.clone())?;
+
+			Self::deposit_event(Event::CycleCreated { bounty, creator: who.clone() });
+			Ok(cycle_id)
+		}
+
+		pub(crate) fn account_id(cycle_id: &T::CycleId) -> T::AccountId {
+			T::PalletId::get().into_sub_account_truncating(cycle_id)
+		}
+
+		/// Minimum native bounty.
+		pub(crate) fn min_bounty() -> BalanceOf<T> {
+			PalletParameters::<T>::get()
+				.map_or_else(T::MinBounty::get, |parameters| parameters.min_bounty)
+		}
+
+		/// Parameters of cycles created without explicit ones.
+		pub(crate) fn default_cycle_parameters() -> CycleParametersOf<T> {
+			match PalletParameters::<T>::get() {
+				Some(parameters) => parameters.cycle_parameters,
+				None => CycleParameters {
+					delay_before_bots: T::DelayBeforeBots::get(),
+					delay_before_second_phase: T::DelayBeforeSecondPhase::get(),
+					second_phase_duration: T::SecondPhaseDuration::get(),
+					max_generators: T::MaxGenerators::get(),
+					deposit: T::Deposit::get(),
+					min_revealers: T::MinRevealers::get(),
+				},
+			}
+		}
+
+		pub fn is_bot(who: &AccountIdOf<T>) -> bool {
+			Bots::<T>::contains_key(who)
+		}
+
+		/// Share of bounty paid to a generator revealing correct secret. What bots do not get of
+		/// their share is left to the treasury.
+		pub(crate) fn generator_share(
+			cycle: &RngCycleOf<T>,
+			is_bot: bool,
+		) -> Result<BalanceOf<T>, DispatchError> {
+			let total_shares = cycle.generators_count + 1; // add one for our profit share
+			let mut share = cycle
+				.bounty
+				.checked_div(&total_shares.saturated_into())
+				.ok_or(ArithmeticError::Underflow)?;
+			if is_bot {
+				share = match BotSharePolicy::<T>::get() {
+					BotShare::Equal => share,
+					BotShare::Reduced(part) => part.mul_floor(share),
+					BotShare::Flat(fee) => fee.min(share),
+				};
+			}
+			Ok(share)
+		}
+
+		/// Number of generators of the cycle who did not reveal their secret and of those who did.
+		pub(crate) fn generator_counts(cycle: &RngCycleOf<T>) -> (u32, u32) {
+			let revealed = cycle.revealed_count;
+			(cycle.generators_count.saturating_sub(revealed).into(), revealed.into())
+		}
+
+		/// Block at which the second phase (reveal) of the cycle starts.
+		pub(crate) fn second_phase_start(cycle: &RngCycleOf<T>) -> BlockNumberOf<T> {
+			Self::commit_phase_end(cycle, cycle.commit_extensions)
+		}
+
+		/// Block at which the commit phase of the cycle ends once extended `extensions` times.
+		fn commit_phase_end(cycle: &RngCycleOf<T>, extensions: u8) -> BlockNumberOf<T> {
+			cycle.started +
+				cycle.params.delay_before_bots +
+				cycle.params.delay_before_second_phase +
+				T::CommitPhaseExtension::get() * extensions.into()
+		}
+
+		/// Deposit of a cycle with `bounty` and `deposit` in its parameters required by the
+		/// `Withholding` policy, at most the one of `MaxCycleParameters`.
+		pub(crate) fn withholding_deposit(
+			deposit: BalanceOf<T>,
+			bounty: &BalanceOf<T>,
+		) -> BalanceOf<T> {
+			T::Withholding::deposit(deposit, bounty).min(T::MaxCycleParameters::get().deposit)
+		}
+
+		/// Revealed secrets the cycle needs to complete, there is no random number without any.
+		pub(crate) fn min_revealers(cycle: &RngCycleOf<T>) -> u8 {
+			cycle.params.min_revealers.max(1)
+		}
+
+		/// Times the commit phase of the cycle is extended by block `now`. It is extended whenever
+		/// it would end with fewer commitments than the cycle needs reveals, as long as
+		/// `MaxCommitPhaseExtensions` allows.
+		pub(crate) fn commit_extensions_at(cycle: &RngCycleOf<T>, now: BlockNumberOf<T>) -> u8 {
+			let mut extensions = cycle.commit_extensions;
+			if T::CommitPhaseExtension::get().is_zero() ||
+				cycle.generators_count >= Self::min_revealers(cycle)
+			{
+				return extensions
+			}
+			while extensions < T::MaxCommitPhaseExtensions::get() &&
+				now >= Self::commit_phase_end(cycle, extensions)
+			{
+				extensions += 1;
+			}
+			extensions
+		}
+
+		/// Brings the cycle to block `now`: counts the extensions of its commit phase due by then
+		/// and updates its status.
+		pub(crate) fn advance(cycle: &mut RngCycleOf<T>, now: BlockNumberOf<T>) {
+			if !cycle.status.is_finalised() {
+				cycle.commit_extensions = Self::commit_extensions_at(cycle, now);
+			}
+			cycle.status = Self::status_at(cycle, now);
+		}
+
+		/// Block from which no more secrets are accepted and the cycle can be finalised.
+		pub(crate) fn cycle_deadline(cycle: &RngCycleOf<T>) -> BlockNumberOf<T> {
+			Self::second_phase_start(cycle) + cycle.params.second_phase_duration
+		}
+
+		/// Schedules the cycle to be finalised by `on_initialize` of block `block`, or of the first
+		/// block after it with room for one more cycle, which only delays its payouts. Returns the
+		/// weight of the blocks looked at.
+		pub(crate) fn schedule_finalisation(
+			cycle_id: T::CycleId,
+			mut block: BlockNumberOf<T>,
+		) -> Weight {
+			let mut reads = 1;
+			while CycleDeadlines::<T>::try_mutate(block, |cycle_ids| cycle_ids.try_push(cycle_id))
+				.is_err()
+			{
+				block = block.saturating_add(One::one());
+				reads += 1;
+			}
+			T::DbWeight::get().reads_writes(reads, 1)
+		}
+
+		/// Pays back creator and contributors of a failed cycle, settles cycle's funds and marks
+		/// the cycle as finalised. Caller must ensure the cycle is `Finished`.
+		pub(crate) fn finalise_cycle(
+			cycle_id: &T::CycleId,
+			cycle: &mut RngCycleOf<T>,
+		) -> DispatchResult {
+			FinalisationRetries::<T>::remove(cycle_id);
+			if cycle.revealed_count < Self::min_revealers(cycle) {
 				// as deadlines have passed and
-				// no one participate or no one revealed
-				// creator gets bounty back
-				T::Balances::transfer(
-					&Self::account_id(&cycle_id.clone()),
-					&who,
-					cycle.bounty,
-					false,
-				)?;
-				Self::deposit_event(Event::<T>::CycleFailed { cycle_id, creator: who });
-				Ok(())
+				// too few participated or revealed
+				// creator gets bounty back, revealers got their deposit back already
+				let _ = UnpaidShares::<T>::clear_prefix(cycle_id, u8::MAX.into(), None);
+				RevealedEntropies::<T>::remove(cycle_id);
+				Self::refund_bounty(cycle_id, cycle)?;
+				Self::settle_cycle(cycle_id, cycle)?;
+				Self::drop_requests(cycle_id);
+				cycle.status = CycleStatus::Failed;
+				Self::deposit_event(Event::<T>::CycleFailed {
+					cycle_id: *cycle_id,
+					creator: cycle.creator.clone(),
+				});
 			} else {
+				Self::settle_cycle(cycle_id, cycle)?;
+				cycle.random_number = T::Combiner::combine(&RevealedEntropies::<T>::take(cycle_id));
+				cycle.status = CycleStatus::Completed;
+				let now = <frame_system::Pallet<T>>::block_number();
+				LatestRandomNumber::<T>::put((cycle.random_number, now));
+				RandomnessHistory::<T>::mutate(|history| {
+					if history.len() >= T::MaxHistory::get() as usize && !history.is_empty() {
+						history.remove(0);
+					}
+					let _ = history.try_push(RandomnessRecord {
+						cycle_id: *cycle_id,
+						block: now,
+						random_number: cycle.random_number,
+					});
+				});
+				Self::queue_callbacks(cycle_id, cycle.random_number);
 				Self::deposit_event(Event::<T>::CycleCompleted {
-					cycle_id,
-					creator: who,
+					cycle_id: *cycle_id,
+					creator: cycle.creator.clone(),
 					random_number: cycle.random_number,
 				});
-				Ok(())
 			}
-			//TODO: In above both case any leftover balance from cycle's account should be
-			//transferred to org's account
+			Ok(())
+		}
+
+		/// Moves everything left of the cycle's funds to the treasury. `slashed` in the emitted
+		/// event is the part coming from deposits of generators who did not reveal, `leftover` is
+		/// the unpaid bounty including our profit share and rounding dust, in the bounty's asset.
+		/// Must be called once all payouts and refunds of the cycle are done.
+		pub(crate) fn settle_cycle(cycle_id: &T::CycleId, cycle: &RngCycleOf<T>) -> DispatchResult {
+			let treasury = T::TreasuryAccount::get();
+			let mut slashed = BalanceOf::<T>::zero();
+			// generators who revealed are not in `Generators` anymore
+			for generator in Generators::<T>::iter_key_prefix(cycle_id) {
+				T::Funds::pay(cycle_id, &generator, &treasury, cycle.params.deposit.clone())?;
+				slashed = slashed.saturating_add(cycle.params.deposit.clone());
+			}
+			let leftover = Self::sweep_bounty(cycle_id, cycle, &treasury)?;
+			BountyContributions::<T>::remove(cycle_id);
+			if slashed.is_zero() && leftover.is_zero() {
+				return Ok(())
+			}
+			Self::deposit_event(Event::CycleSettled { cycle_id: *cycle_id, slashed, leftover });
+			Ok(())
+		}
+
+		/// Sets `amount` of `from` aside as bounty of the cycle created by `creator`, in the
+		/// cycle's account if it is paid in an asset.
+		fn hold_bounty(
+			cycle_id: &T::CycleId,
+			creator: &AccountIdOf<T>,
+			from: &AccountIdOf<T>,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			match BountyAssets::<T>::get(cycle_id) {
+				Some(asset) => {
+					T::Assets::transfer(asset, from, &Self::account_id(cycle_id), amount, true)?;
+					Ok(())
+				},
+				None => T::Funds::hold_for(cycle_id, from, creator, amount),
+			}
+		}
+
+		/// Pays `amount` of the bounty of the cycle to `to`.
+		fn pay_bounty(
+			cycle_id: &T::CycleId,
+			cycle: &RngCycleOf<T>,
+			to: &AccountIdOf<T>,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			match BountyAssets::<T>::get(cycle_id) {
+				Some(asset) => {
+					T::Assets::transfer(asset, &Self::account_id(cycle_id), to, amount, false)?;
+					Ok(())
+				},
+				None => T::Funds::pay(cycle_id, &cycle.creator, to, amount),
+			}
+		}
+
+		/// Pays the bounty of a cancelled or failed cycle back, top-ups to those who contributed
+		/// them and the rest to the creator.
+		pub(crate) fn refund_bounty(
+			cycle_id: &T::CycleId,
+			cycle: &RngCycleOf<T>,
+		) -> DispatchResult {
+			let mut rest = cycle.bounty.clone();
+			for (contributor, amount) in BountyContributions::<T>::get(cycle_id) {
+				Self::pay_bounty(cycle_id, cycle, &contributor, amount.clone())?;
+				rest = rest.saturating_sub(amount);
+			}
+			Self::pay_bounty(cycle_id, cycle, &cycle.creator, rest)
+		}
+
+		/// Adds `amount` to what `who` contributed to the bounty of the cycle.
+		fn record_contribution(
+			cycle_id: &T::CycleId,
+			who: &AccountIdOf<T>,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			BountyContributions::<T>::try_mutate(cycle_id, |contributions| {
+				if let Some((_, total)) =
+					contributions.iter_mut().find(|(contributor, _)| contributor == who)
+				{
+					*total = total.checked_add(&amount).ok_or(ArithmeticError::Overflow)?;
+					return Ok(())
+				}
+				contributions
+					.try_push((who.clone(), amount))
+					.map_err(|_| Error::<T>::TooManyContributors.into())
+			})
+		}
+
+		/// Number of accounts other than its creator which topped up the bounty of the cycle.
+		pub(crate) fn contributor_count(cycle_id: &T::CycleId) -> u32 {
+			BountyContributions::<T>::decode_len(cycle_id).unwrap_or_default() as u32
+		}
+
+		/// Pays what is left of the funds of the cycle to `to` and returns what was left of the
+		/// bounty. A leftover in an asset which `to` can not receive, like dust below the minimum
+		/// balance of an account `to` does not have, stays in the cycle's account, which is kept
+		/// alive for it.
+		fn sweep_bounty(
+			cycle_id: &T::CycleId,
+			cycle: &RngCycleOf<T>,
+			to: &AccountIdOf<T>,
+		) -> Result<BalanceOf<T>, DispatchError> {
+			let leftover = T::Funds::sweep(cycle_id, &cycle.creator, to)?;
+			match BountyAssets::<T>::get(cycle_id) {
+				Some(asset) => {
+					let cycle_account = Self::account_id(cycle_id);
+					let total = T::Assets::balance(asset, &cycle_account);
+					if !total.is_zero() &&
+						T::Assets::can_deposit(asset, to, total, false) !=
+							DepositConsequence::Success
+					{
+						return Ok(Zero::zero())
+					}
+					let swept = if total.is_zero() {
+						total
+					} else {
+						T::Assets::transfer(asset, &cycle_account, to, total, false)?
+					};
+					let _ = frame_system::Pallet::<T>::dec_providers(&cycle_account);
+					Ok(swept)
+				},
+				None => Ok(leftover),
+			}
+		}
+
+		/// Status of the cycle at block `now`, including extensions of its commit phase due by
+		/// then. Finalised cycles never change their status.
+		pub(crate) fn status_at(cycle: &RngCycleOf<T>, now: BlockNumberOf<T>) -> CycleStatus {
+			if cycle.status.is_finalised() {
+				return cycle.status
+			}
+			let extended = RngCycle {
+				commit_extensions: Self::commit_extensions_at(cycle, now),
+				..cycle.clone()
+			};
+			if now >= Self::cycle_deadline(&extended) {
+				CycleStatus::Finished
+			} else if now >= Self::second_phase_start(&extended) {
+				CycleStatus::RevealPhase
+			} else if now > cycle.started + cycle.params.delay_before_bots {
+				CycleStatus::BotCommitPhase
+			} else {
+				CycleStatus::CommitPhase
+			}
 		}
 	}
 
+	// Off-chain worker bot.
 	impl<T: Config> Pallet<T> {
-		pub(crate) fn account_id(cycle_id: &T::CycleId) -> T::AccountId {
-			T::PalletId::get().into_sub_account_truncating(cycle_id)
+		pub(crate) fn bot_commitment_key(cycle_id: &T::CycleId) -> Vec<u8> {
+			(b"rng-dao::bot-commitment", cycle_id).encode()
+		}
+
+		/// Commits to a fresh secret unless this node already did so for the cycle. Returns
+		/// whether a commitment was sent.
+		fn offchain_commit(
+			signer: &Signer<T, T::AuthorityId>,
+			cycle_id: T::CycleId,
+			storage: StorageValueRef,
+		) -> bool {
+			if let Ok(Some(_)) = storage.get::<BotCommitment<AccountIdOf<T>, T::Public>>() {
+				return false
+			}
+			let secret: Secret =
+				sp_io::offchain::random_seed().to_vec().try_into().unwrap_or_default();
+			let salt: Salt = sp_io::offchain::random_seed();
+			let sent = signer.send_signed_transaction(|account| Call::send_hash {
+				cycle_id,
+				hash: crate::commitment::commitment(&cycle_id, &account.id, &salt, &secret),
+			});
+			if let Some((account, Ok(()))) = sent {
+				storage.set(&BotCommitment {
+					account: account.id,
+					public: account.public,
+					secret,
+					salt,
+				});
+				return true
+			}
+			false
+		}
+
+		/// Reveals the secret committed by this node, with the key it was committed with. Sent
+		/// again every block until the reveal is included.
+		fn offchain_reveal(cycle_id: T::CycleId, mut storage: StorageValueRef) {
+			let commitment = match storage.get::<BotCommitment<AccountIdOf<T>, T::Public>>() {
+				Ok(Some(commitment)) => commitment,
+				_ => return,
+			};
+			if !Generators::<T>::contains_key(cycle_id, &commitment.account) {
+				// revealed already, the cycle may be finalised before the worker sees it again
+				storage.clear();
+				return
+			}
+			let _ = Signer::<T, T::AuthorityId>::any_account()
+				.with_filter(sp_std::vec![commitment.public])
+				.send_signed_transaction(|_| Call::reveal_secret {
+					cycle_id,
+					secret: commitment.secret.clone(),
+					salt: commitment.salt,
+				});
+		}
+	}
+
+	// Invariants checked by `try_state`.
+	#[cfg(any(feature = "try-runtime", test))]
+	impl<T: Config> Pallet<T> {
+		/// Checks that every cycle has an id below `CycleCount`, that its generators are the ones
+		/// in `Generators` and the ones who revealed, and that a running cycle still holds its
+		/// deposits and what is left of its bounty. Also checks that `PendingRequests` and
+		/// `ConsumerRequests` count all requests for randomness and that finalised cycles have
+		/// none left.
+		pub(crate) fn do_try_state() -> Result<(), &'static str> {
+			let cycle_count = CycleCount::<T>::get();
+			for (cycle_id, cycle) in Cycles::<T>::iter() {
+				ensure!(cycle_id < cycle_count, "cycle id is not below cycle count");
+				ensure!(
+					cycle.revealed_count <= cycle.generators_count,
+					"more reveals than generators"
+				);
+				let mut holders: Vec<_> = Generators::<T>::iter_key_prefix(cycle_id).collect();
+				let unrevealed = holders.len();
+				ensure!(
+					unrevealed + usize::from(cycle.revealed_count) ==
+						usize::from(cycle.generators_count),
+					"generators count does not match generators and reveals"
+				);
+				// generators who revealed before `Reveals` was introduced are in neither map
+				ensure!(
+					Reveals::<T>::iter_key_prefix(cycle_id).count() <=
+						usize::from(cycle.revealed_count),
+					"more reveals stored than revealed"
+				);
+				if cycle.status.is_finalised() {
+					ensure!(
+						!RandomnessRequests::<T>::contains_key(cycle_id),
+						"finalised cycle has requests for randomness"
+					);
+					ensure!(
+						!FinalisationRetries::<T>::contains_key(cycle_id),
+						"finalised cycle is retried"
+					);
+					ensure!(
+						!BountyContributions::<T>::contains_key(cycle_id),
+						"finalised cycle has contributions to its bounty"
+					);
+					continue
+				}
+				let contributed = BountyContributions::<T>::get(cycle_id)
+					.into_iter()
+					.fold(BalanceOf::<T>::zero(), |total, (_, amount)| {
+						total.saturating_add(amount)
+					});
+				ensure!(contributed <= cycle.bounty, "contributions exceed the bounty");
+
+				let deposits = cycle.params.deposit.saturating_mul(unrevealed.saturated_into());
+				let unpaid = UnpaidShares::<T>::iter_key_prefix(cycle_id).count();
+				let paid = usize::from(cycle.revealed_count).saturating_sub(unpaid);
+				// shares of bots are at most the ones of humans
+				let share = Self::generator_share(&cycle, false).map_err(|_| "share overflows")?;
+				let bounty =
+					cycle.bounty.saturating_sub(share.saturating_mul(paid.saturated_into()));
+				if !holders.contains(&cycle.creator) {
+					holders.push(cycle.creator.clone());
+				}
+				let held = T::Funds::held(&cycle_id, &holders);
+				match BountyAssets::<T>::get(cycle_id) {
+					Some(asset) => {
+						ensure!(held >= deposits, "cycle does not hold its deposits");
+						ensure!(
+							T::Assets::balance(asset, &Self::account_id(&cycle_id)) >= bounty,
+							"cycle does not hold its bounty"
+						);
+					},
+					None => ensure!(
+						held >= bounty.saturating_add(deposits),
+						"cycle does not hold its bounty and deposits"
+					),
+				}
+			}
+			let requests: usize = RandomnessRequests::<T>::iter_values().map(|r| r.len()).sum();
+			ensure!(
+				requests + QueuedCallbacks::<T>::get().len() ==
+					PendingRequests::<T>::get() as usize,
+				"pending requests do not match requests and queued callbacks"
+			);
+			let callbacks: Vec<CallbackId> = RandomnessRequests::<T>::iter_values()
+				.flatten()
+				.chain(QueuedCallbacks::<T>::get().into_iter().map(|(request, _)| request))
+				.map(|request| request.callback)
+				.collect();
+			for callback in &callbacks {
+				ensure!(
+					callbacks.iter().filter(|other| *other == callback).count() ==
+						ConsumerRequests::<T>::get(callback) as usize,
+					"consumer requests do not match requests and queued callbacks"
+				);
+			}
+			ensure!(
+				ConsumerRequests::<T>::iter_values().map(|count| count as usize).sum::<usize>() ==
+					callbacks.len(),
+				"consumer requests do not match requests and queued callbacks"
+			);
+			Ok(())
+		}
+	}
+
+	// Randomness requested by other pallets, see `crate::consumer`.
+	impl<T: Config> Pallet<T> {
+		/// Requests the random number of cycle `cycle_id` for the consumer going by `callback`,
+		/// which is called with it once the cycle completes. Only cycles in their commit phase
+		/// accept requests, before anyone can know their random number. If the cycle fails or is
+		/// cancelled the request is dropped without calling the consumer.
+		pub fn request_randomness(
+			callback: CallbackId,
+			cycle_id: T::CycleId,
+		) -> Result<RequestId, DispatchError> {
+			let max_weight =
+				T::Consumers::max_weight(&callback).ok_or(Error::<T>::UnknownCallback)?;
+			ensure!(max_weight.all_lte(T::MaxCallbackWeight::get()), Error::<T>::CallbackTooHeavy);
+			let cycle = Cycles::<T>::get(cycle_id).ok_or(Error::<T>::NoCycleFound)?;
+			ensure!(
+				matches!(
+					Self::status_at(&cycle, <frame_system::Pallet<T>>::block_number()),
+					CycleStatus::CommitPhase | CycleStatus::BotCommitPhase
+				),
+				Error::<T>::CommitPhaseOver
+			);
+			let pending = PendingRequests::<T>::get();
+			ensure!(pending < T::MaxPendingRequests::get(), Error::<T>::TooManyRequests);
+			let consumer_requests = ConsumerRequests::<T>::get(callback);
+			ensure!(
+				consumer_requests < T::MaxRequestsPerConsumer::get(),
+				Error::<T>::TooManyConsumerRequests
+			);
+			let request_id = NextRequestId::<T>::get();
+			RandomnessRequests::<T>::try_mutate(cycle_id, |requests| {
+				requests.try_push(RandomnessRequest { id: request_id, callback })
+			})
+			.map_err(|_| Error::<T>::TooManyRequests)?;
+			PendingRequests::<T>::put(pending + 1);
+			ConsumerRequests::<T>::insert(callback, consumer_requests + 1);
+			NextRequestId::<T>::put(request_id.wrapping_add(1));
+			Self::deposit_event(Event::RandomnessRequested { request_id, cycle_id, callback });
+			Ok(request_id)
+		}
+
+		/// Queues the requests for the random number of a cycle which just completed.
+		fn queue_callbacks(cycle_id: &T::CycleId, random_number: H256) {
+			let requests = RandomnessRequests::<T>::take(cycle_id);
+			if requests.is_empty() {
+				return
+			}
+			QueuedCallbacks::<T>::mutate(|queue| {
+				for request in requests {
+					// `PendingRequests` keeps all requests within the queue's bound
+					let _ = queue.try_push((request, random_number));
+				}
+			});
+		}
+
+		/// Drops the requests for the random number of a cycle which failed or was cancelled.
+		fn drop_requests(cycle_id: &T::CycleId) {
+			let requests = RandomnessRequests::<T>::take(cycle_id);
+			if requests.is_empty() {
+				return
+			}
+			PendingRequests::<T>::mutate(|pending| {
+				*pending = pending.saturating_sub(requests.len() as u32)
+			});
+			for request in requests {
+				Self::release_request(&request);
+			}
+			Self::deposit_event(Event::RandomnessRequestsDropped { cycle_id: *cycle_id });
+		}
+
+		/// Counts a request which was delivered or dropped out of the requests of its consumer.
+		fn release_request(request: &RandomnessRequest) {
+			ConsumerRequests::<T>::mutate_exists(request.callback, |count| {
+				*count = count.filter(|count| *count > 1).map(|count| count - 1)
+			});
+		}
+
+		/// Calls consumers of queued requests, oldest first, as long as their `max_weight` fits in
+		/// `limit`. The others stay queued for the next blocks. Requests which can never be
+		/// delivered, of consumers no longer in the runtime or whose `max_weight` exceeds
+		/// `MaxCallbackWeight` since a runtime upgrade, are dropped so that they do not hold up
+		/// the queue.
+		pub(crate) fn deliver_randomness(limit: Weight) -> Weight {
+			let mut queue = QueuedCallbacks::<T>::get();
+			if queue.is_empty() {
+				return T::DbWeight::get().reads(1)
+			}
+			let mut used = Weight::zero();
+			let mut handled = 0_u32;
+			for (request, random_number) in queue.iter() {
+				match T::Consumers::max_weight(&request.callback) {
+					Some(max_weight) if max_weight.all_lte(T::MaxCallbackWeight::get()) => {
+						if used.saturating_add(max_weight).any_gt(limit) {
+							break
+						}
+						let actual = T::Consumers::on_randomness(
+							&request.callback,
+							request.id,
+							*random_number,
+						)
+						.unwrap_or(max_weight);
+						used = used.saturating_add(actual);
+						Self::deposit_event(Event::RandomnessDelivered { request_id: request.id });
+					},
+					_ => Self::deposit_event(Event::RandomnessRequestDropped {
+						request_id: request.id,
+					}),
+				}
+				Self::release_request(request);
+				handled += 1;
+			}
+			let mut index = 0;
+			queue.retain(|_| {
+				index += 1;
+				index > handled
+			});
+			QueuedCallbacks::<T>::put(queue);
+			PendingRequests::<T>::mutate(|pending| *pending = pending.saturating_sub(handled));
+			used.saturating_add(T::WeightInfo::deliver_randomness(handled))
+		}
+	}
+
+	/// Randomness derived from the random number of the latest completed cycle. The returned
+	/// block is the one in which that cycle was finalised, zero if no cycle completed yet.
+	impl<T: Config> Randomness<H256, BlockNumberOf<T>> for Pallet<T> {
+		fn random(subject: &[u8]) -> (H256, BlockNumberOf<T>) {
+			let (random_number, known_since) =
+				LatestRandomNumber::<T>::get().unwrap_or((H256::zero(), Zero::zero()));
+			(Keccak256::hash_of(&(subject, random_number)), known_since)
+		}
+	}
+
+	// Queries used by the runtime API.
+	impl<T: Config> Pallet<T> {
+		pub fn cycle_details(
+			cycle_id: T::CycleId,
+		) -> Option<CycleDetails<AccountIdOf<T>, BalanceOf<T>, BlockNumberOf<T>>> {
+			let mut cycle = Cycles::<T>::get(cycle_id)?;
+			Self::advance(&mut cycle, <frame_system::Pallet<T>>::block_number());
+			Some(CycleDetails {
+				phase: cycle.status,
+				second_phase_start: Self::second_phase_start(&cycle),
+				deadline: Self::cycle_deadline(&cycle),
+				cycle,
+			})
+		}
+
+		pub fn cycle_generators(cycle_id: T::CycleId) -> Vec<GeneratorDetails<AccountIdOf<T>>> {
+			Generators::<T>::iter_key_prefix(cycle_id)
+				.map(|account| GeneratorDetails { account, revealed: false })
+				.chain(
+					Reveals::<T>::iter_key_prefix(cycle_id)
+						.map(|account| GeneratorDetails { account, revealed: true }),
+				)
+				.collect()
+		}
+
+		/// Cycles in which `who` committed a hash and can still reveal the secret.
+		pub fn open_commitments(who: AccountIdOf<T>) -> Vec<T::CycleId> {
+			let now = <frame_system::Pallet<T>>::block_number();
+			Generators::<T>::iter_keys()
+				.filter(|(_, account)| *account == who)
+				.map(|(cycle_id, _)| cycle_id)
+				.filter(|cycle_id| {
+					Cycles::<T>::get(cycle_id).map_or(false, |cycle| {
+						let status = Self::status_at(&cycle, now);
+						!status.is_finalised() && status != CycleStatus::Finished
+					})
+				})
+				.collect()
+		}
+
+		/// Random number known as of block `block`, the one of the latest cycle completed by then.
+		/// `None` if no cycle in `RandomnessHistory` completed by then.
+		pub fn randomness_at(block: BlockNumberOf<T>) -> Option<RandomnessRecordOf<T>> {
+			RandomnessHistory::<T>::get()
+				.into_iter()
+				.rev()
+				.find(|record| record.block <= block)
+		}
+
+		/// What `who` gets for revealing the secret in time, `None` if there is nothing to reveal.
+		pub fn predicted_payout(
+			cycle_id: T::CycleId,
+			who: AccountIdOf<T>,
+		) -> Option<PredictedPayoutOf<T>> {
+			let generator = Generators::<T>::get(cycle_id, who)?;
+			let cycle = Cycles::<T>::get(cycle_id)?;
+			let share = Self::generator_share(&cycle, generator.is_bot).ok()?;
+			Some(PredictedPayout {
+				deposit: cycle.params.deposit,
+				share,
+				asset: BountyAssets::<T>::get(cycle_id),
+			})
 		}
 	}
 }
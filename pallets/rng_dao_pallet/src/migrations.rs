@@ -0,0 +1,561 @@
+//! Storage migrations for the rng-dao pallet.
+//!
+//! Every migration runs only on the storage version it was written for. With `try-runtime` its
+//! `post_upgrade` checks that nothing stored before it ran was lost or left undecodable.
+
+/// Decodes the state handed from `pre_upgrade` of a migration to its `post_upgrade`.
+#[cfg(feature = "try-runtime")]
+fn decode_state<S: codec::Decode>(state: sp_std::vec::Vec<u8>) -> Result<S, &'static str> {
+	S::decode(&mut &state[..]).map_err(|_| "pre-upgrade state can not be decoded")
+}
+
+pub mod v1 {
+	use crate::pallet::{AccountIdOf, BalanceOf, BlockNumberOf, Config, CycleStatus, Pallet};
+	use frame_support::{
+		pallet_prelude::*,
+		storage_alias,
+		traits::{fungible::Inspect, GetStorageVersion, OnRuntimeUpgrade},
+	};
+	use sp_std::marker::PhantomData;
+	#[cfg(feature = "try-runtime")]
+	use sp_std::vec::Vec;
+
+	/// `RngCycle` as stored before `status` was introduced.
+	#[derive(Encode, Decode)]
+	pub(crate) struct OldRngCycle<AccountId, Balance, BlockNumber, RandomNumber> {
+		pub creator: AccountId,
+		pub bounty: Balance,
+		pub started: BlockNumber,
+		pub random_number: RandomNumber,
+		pub generators_count: u8,
+		pub revealed_count: u8,
+	}
+
+	pub(crate) type OldRngCycleOf<T> =
+		OldRngCycle<AccountIdOf<T>, BalanceOf<T>, BlockNumberOf<T>, u64>;
+
+	/// `RngCycle` as of storage version 1.
+	#[derive(Encode, Decode)]
+	pub(crate) struct RngCycle<AccountId, Balance, BlockNumber, RandomNumber> {
+		pub creator: AccountId,
+		pub bounty: Balance,
+		pub started: BlockNumber,
+		pub random_number: RandomNumber,
+		pub generators_count: u8,
+		pub revealed_count: u8,
+		pub status: CycleStatus,
+	}
+
+	pub(crate) type RngCycleOf<T> = RngCycle<AccountIdOf<T>, BalanceOf<T>, BlockNumberOf<T>, u64>;
+
+	#[storage_alias]
+	pub(crate) type Cycles<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, <T as Config>::CycleId, RngCycleOf<T>>;
+
+	/// Status of a version 1 cycle, all of them use the runtime constants for their deadlines.
+	fn status_at<T: Config>(cycle: &RngCycleOf<T>, now: BlockNumberOf<T>) -> CycleStatus {
+		let second_phase_start =
+			cycle.started + T::DelayBeforeBots::get() + T::DelayBeforeSecondPhase::get();
+		if now >= second_phase_start + T::SecondPhaseDuration::get() {
+			CycleStatus::Finished
+		} else if now >= second_phase_start {
+			CycleStatus::RevealPhase
+		} else if now > cycle.started + T::DelayBeforeBots::get() {
+			CycleStatus::BotCommitPhase
+		} else {
+			CycleStatus::CommitPhase
+		}
+	}
+
+	/// Adds `status` to every stored cycle.
+	///
+	/// Status of a running cycle is derived from its deadlines. A cycle whose deadlines have passed
+	/// is marked `Completed` if anyone revealed, as finalising it again only re-emits an event.
+	/// Otherwise it is marked `Failed` when the bounty has already left the cycle's account, so
+	/// that creator can not be refunded twice.
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() >= 1 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let now = frame_system::Pallet::<T>::block_number();
+			let mut translated = 0_u64;
+			Cycles::<T>::translate::<OldRngCycleOf<T>, _>(|cycle_id, old| {
+				translated += 1;
+				let mut cycle = RngCycle {
+					creator: old.creator,
+					bounty: old.bounty,
+					started: old.started,
+					random_number: old.random_number,
+					generators_count: old.generators_count,
+					revealed_count: old.revealed_count,
+					status: CycleStatus::CommitPhase,
+				};
+				cycle.status = status_at::<T>(&cycle, now);
+				if cycle.status == CycleStatus::Finished {
+					if cycle.revealed_count > 0 {
+						cycle.status = CycleStatus::Completed;
+					} else if T::Balances::balance(&Pallet::<T>::account_id(&cycle_id)) <
+						cycle.bounty
+					{
+						cycle.status = CycleStatus::Failed;
+					}
+				}
+				Some(cycle)
+			});
+
+			StorageVersion::new(1).put::<Pallet<T>>();
+			T::DbWeight::get().reads_writes(translated * 2 + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((Pallet::<T>::on_chain_storage_version(), Cycles::<T>::iter_keys().count() as u64)
+				.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let (version, cycles): (StorageVersion, u64) = super::decode_state(state)?;
+			if version >= 1 {
+				return Ok(())
+			}
+			ensure!(Pallet::<T>::on_chain_storage_version() == 1, "storage version not updated");
+			ensure!(Cycles::<T>::iter_values().count() as u64 == cycles, "cycles lost");
+			Ok(())
+		}
+	}
+}
+
+pub mod v2 {
+	use super::v1;
+	use crate::pallet::{AccountIdOf, BalanceOf, BlockNumberOf, Config, CycleStatus, Pallet};
+	use frame_support::{
+		pallet_prelude::*,
+		storage_alias,
+		traits::{GetStorageVersion, OnRuntimeUpgrade},
+	};
+	use sp_std::marker::PhantomData;
+	#[cfg(feature = "try-runtime")]
+	use sp_std::vec::Vec;
+
+	/// `CycleParameters` as of storage version 2.
+	#[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
+	pub(crate) struct CycleParameters<Balance, BlockNumber> {
+		pub delay_before_bots: BlockNumber,
+		pub delay_before_second_phase: BlockNumber,
+		pub second_phase_duration: BlockNumber,
+		pub max_generators: u8,
+		pub deposit: Balance,
+	}
+
+	/// Parameters of a version 2 cycle created with the runtime constants.
+	pub(crate) fn default_cycle_parameters<T: Config>(
+	) -> CycleParameters<BalanceOf<T>, BlockNumberOf<T>> {
+		CycleParameters {
+			delay_before_bots: T::DelayBeforeBots::get(),
+			delay_before_second_phase: T::DelayBeforeSecondPhase::get(),
+			second_phase_duration: T::SecondPhaseDuration::get(),
+			max_generators: T::MaxGenerators::get(),
+			deposit: T::Deposit::get(),
+		}
+	}
+
+	/// `RngCycle` as of storage version 2.
+	#[derive(Encode, Decode)]
+	pub(crate) struct RngCycle<AccountId, Balance, BlockNumber, RandomNumber> {
+		pub creator: AccountId,
+		pub bounty: Balance,
+		pub started: BlockNumber,
+		pub random_number: RandomNumber,
+		pub generators_count: u8,
+		pub revealed_count: u8,
+		pub status: CycleStatus,
+		pub params: CycleParameters<Balance, BlockNumber>,
+	}
+
+	pub(crate) type RngCycleOf<T> = RngCycle<AccountIdOf<T>, BalanceOf<T>, BlockNumberOf<T>, u64>;
+
+	#[storage_alias]
+	pub(crate) type Cycles<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, <T as Config>::CycleId, RngCycleOf<T>>;
+
+	/// Adds `params` to every stored cycle. Cycles created so far used the runtime constants,
+	/// which are exactly the default parameters.
+	pub struct MigrateToV2<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 1 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut translated = 0_u64;
+			Cycles::<T>::translate::<v1::RngCycleOf<T>, _>(|_, old| {
+				translated += 1;
+				Some(RngCycle {
+					creator: old.creator,
+					bounty: old.bounty,
+					started: old.started,
+					random_number: old.random_number,
+					generators_count: old.generators_count,
+					revealed_count: old.revealed_count,
+					status: old.status,
+					params: default_cycle_parameters::<T>(),
+				})
+			});
+
+			StorageVersion::new(2).put::<Pallet<T>>();
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((Pallet::<T>::on_chain_storage_version(), Cycles::<T>::iter_keys().count() as u64)
+				.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let (version, cycles): (StorageVersion, u64) = super::decode_state(state)?;
+			if version != 1 {
+				return Ok(())
+			}
+			ensure!(Pallet::<T>::on_chain_storage_version() == 2, "storage version not updated");
+			let params = default_cycle_parameters::<T>();
+			let mut migrated = 0_u64;
+			for cycle in Cycles::<T>::iter_values() {
+				ensure!(cycle.params == params, "cycle without default parameters");
+				migrated += 1;
+			}
+			ensure!(migrated == cycles, "cycles lost");
+			Ok(())
+		}
+	}
+}
+
+pub mod v3 {
+	use crate::{
+		commitment::{CommitmentVersion, Secret},
+		pallet::{Config, Generator, Generators, Pallet, Reveals},
+	};
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{GetStorageVersion, OnRuntimeUpgrade},
+	};
+	use sp_core::H256;
+	use sp_std::marker::PhantomData;
+	#[cfg(feature = "try-runtime")]
+	use sp_std::vec::Vec;
+
+	/// `Generator` as stored before salted secrets were introduced.
+	#[derive(Encode, Decode)]
+	pub(crate) struct OldGenerator {
+		pub secret: u64,
+		pub hash: H256,
+		pub is_bot: bool,
+	}
+
+	/// Marks commitments of every stored generator as `Legacy` and stores revealed `u64`
+	/// secrets as their little-endian bytes.
+	pub struct MigrateToV3<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV3<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 2 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut translated = 0_u64;
+			Generators::<T>::translate::<OldGenerator, _>(|_, _, old| {
+				translated += 1;
+				Some(Generator {
+					hash: old.hash,
+					is_bot: old.is_bot,
+					version: CommitmentVersion::Legacy,
+				})
+			});
+			Reveals::<T>::translate::<u64, _>(|_, _, secret| {
+				translated += 1;
+				Some(Secret::try_from(secret.to_le_bytes().to_vec()).unwrap_or_default())
+			});
+
+			StorageVersion::new(3).put::<Pallet<T>>();
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((
+				Pallet::<T>::on_chain_storage_version(),
+				Generators::<T>::iter_keys().count() as u64,
+				Reveals::<T>::iter_keys().count() as u64,
+			)
+				.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let (version, generators, reveals): (StorageVersion, u64, u64) =
+				super::decode_state(state)?;
+			if version != 2 {
+				return Ok(())
+			}
+			ensure!(Pallet::<T>::on_chain_storage_version() == 3, "storage version not updated");
+			ensure!(Generators::<T>::iter_values().count() as u64 == generators, "generators lost");
+			ensure!(Reveals::<T>::iter_values().count() as u64 == reveals, "reveals lost");
+			Ok(())
+		}
+	}
+}
+
+pub mod v4 {
+	use super::v2;
+	use crate::pallet::{
+		self, AccountIdOf, BalanceOf, BlockNumberOf, Config, Pallet, RevealedEntropies,
+	};
+	use frame_support::{
+		pallet_prelude::*,
+		storage_alias,
+		traits::{GetStorageVersion, OnRuntimeUpgrade},
+	};
+	use sp_core::H256;
+	use sp_std::marker::PhantomData;
+	#[cfg(feature = "try-runtime")]
+	use sp_std::vec::Vec;
+
+	/// `RngCycle` as of storage version 4.
+	pub(crate) type RngCycleOf<T> =
+		v2::RngCycle<AccountIdOf<T>, BalanceOf<T>, BlockNumberOf<T>, H256>;
+
+	#[storage_alias]
+	pub(crate) type Cycles<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, <T as Config>::CycleId, RngCycleOf<T>>;
+
+	#[storage_alias]
+	pub(crate) type LatestRandomNumber<T: Config> =
+		StorageValue<Pallet<T>, (u64, BlockNumberOf<T>)>;
+
+	/// `u64` random number zero padded to the 32 bytes of entropy of a legacy secret, see
+	/// `CommitmentVersion::entropy`.
+	fn widen(random_number: u64) -> H256 {
+		let mut widened = H256::zero();
+		widened[..8].copy_from_slice(&random_number.to_le_bytes());
+		widened
+	}
+
+	/// Widens stored random numbers to `H256`.
+	///
+	/// Random number of a running cycle is the XOR of the secrets revealed so far. It is kept as a
+	/// single entropy of the cycle, which the `Combiner` combines with the secrets revealed later.
+	pub struct MigrateToV4<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV4<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 3 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut translated = 0_u64;
+			let mut running = 0_u64;
+			Cycles::<T>::translate::<v2::RngCycleOf<T>, _>(|cycle_id, old| {
+				translated += 1;
+				if !old.status.is_finalised() && old.revealed_count > 0 {
+					running += 1;
+					let _ = RevealedEntropies::<T>::try_append(cycle_id, widen(old.random_number));
+				}
+				Some(v2::RngCycle {
+					creator: old.creator,
+					bounty: old.bounty,
+					started: old.started,
+					random_number: if old.status.is_finalised() {
+						widen(old.random_number)
+					} else {
+						H256::zero()
+					},
+					generators_count: old.generators_count,
+					revealed_count: old.revealed_count,
+					status: old.status,
+					params: old.params,
+				})
+			});
+			if let Some((random_number, known_since)) = LatestRandomNumber::<T>::take() {
+				pallet::LatestRandomNumber::<T>::put((widen(random_number), known_since));
+			}
+
+			StorageVersion::new(4).put::<Pallet<T>>();
+			T::DbWeight::get().reads_writes(translated + 2, translated + running + 2)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((
+				Pallet::<T>::on_chain_storage_version(),
+				Cycles::<T>::iter_keys().count() as u64,
+				LatestRandomNumber::<T>::exists(),
+			)
+				.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let (version, cycles, latest): (StorageVersion, u64, bool) =
+				super::decode_state(state)?;
+			if version != 3 {
+				return Ok(())
+			}
+			ensure!(Pallet::<T>::on_chain_storage_version() == 4, "storage version not updated");
+			ensure!(Cycles::<T>::iter_values().count() as u64 == cycles, "cycles lost");
+			ensure!(
+				pallet::LatestRandomNumber::<T>::get().is_some() == latest,
+				"latest random number lost"
+			);
+			Ok(())
+		}
+	}
+}
+
+pub mod v5 {
+	use super::v4;
+	use crate::pallet::{Config, CycleParameters, Cycles, Pallet, RngCycle};
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{GetStorageVersion, OnRuntimeUpgrade},
+	};
+	use sp_std::marker::PhantomData;
+	#[cfg(feature = "try-runtime")]
+	use sp_std::vec::Vec;
+
+	/// Adds `min_revealers` and `commit_extensions` to every stored cycle.
+	///
+	/// Stored cycles keep completing with a single revealed secret, as they did when they were
+	/// created, and none of them has been extended.
+	pub struct MigrateToV5<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV5<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 4 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut translated = 0_u64;
+			Cycles::<T>::translate::<v4::RngCycleOf<T>, _>(|_, old| {
+				translated += 1;
+				Some(RngCycle {
+					creator: old.creator,
+					bounty: old.bounty,
+					started: old.started,
+					random_number: old.random_number,
+					generators_count: old.generators_count,
+					revealed_count: old.revealed_count,
+					status: old.status,
+					params: CycleParameters {
+						delay_before_bots: old.params.delay_before_bots,
+						delay_before_second_phase: old.params.delay_before_second_phase,
+						second_phase_duration: old.params.second_phase_duration,
+						max_generators: old.params.max_generators,
+						deposit: old.params.deposit,
+						min_revealers: 1,
+					},
+					commit_extensions: 0,
+				})
+			});
+
+			StorageVersion::new(5).put::<Pallet<T>>();
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((Pallet::<T>::on_chain_storage_version(), Cycles::<T>::iter_keys().count() as u64)
+				.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let (version, cycles): (StorageVersion, u64) = super::decode_state(state)?;
+			if version != 4 {
+				return Ok(())
+			}
+			ensure!(Pallet::<T>::on_chain_storage_version() == 5, "storage version not updated");
+			ensure!(Cycles::<T>::iter_values().count() as u64 == cycles, "cycles lost");
+			Ok(())
+		}
+	}
+}
+
+pub mod v6 {
+	use crate::pallet::{Config, CycleDeadlines, Cycles, Pallet};
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{GetStorageVersion, OnRuntimeUpgrade},
+	};
+	use sp_std::{marker::PhantomData, vec::Vec};
+
+	/// Schedules every running cycle to be finalised by `on_initialize`.
+	///
+	/// Cycles started before `CycleDeadlines` was introduced are not in it, so they were only
+	/// finalised when their creator called `get_random_number`. A cycle whose deadline has passed
+	/// is finalised in the block of the upgrade, as migrations run before `on_initialize`.
+	pub struct MigrateToV6<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV6<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 5 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let now = frame_system::Pallet::<T>::block_number();
+			// cycles may be scheduled after their deadline when its block was full
+			let mut deadlines = 0_u64;
+			let mut already_scheduled = Vec::new();
+			for cycle_ids in CycleDeadlines::<T>::iter_values() {
+				deadlines += 1;
+				already_scheduled.extend(cycle_ids);
+			}
+			let mut cycles = 0_u64;
+			let mut weight = Weight::zero();
+			for (cycle_id, cycle) in Cycles::<T>::iter() {
+				cycles += 1;
+				if cycle.status.is_finalised() || already_scheduled.contains(&cycle_id) {
+					continue
+				}
+				let deadline = Pallet::<T>::cycle_deadline(&cycle);
+				weight = weight.saturating_add(Pallet::<T>::schedule_finalisation(
+					cycle_id,
+					deadline.max(now),
+				));
+			}
+
+			StorageVersion::new(6).put::<Pallet<T>>();
+			weight.saturating_add(T::DbWeight::get().reads_writes(cycles + deadlines + 1, 1))
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((Pallet::<T>::on_chain_storage_version(), Cycles::<T>::iter_keys().count() as u64)
+				.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let (version, cycles): (StorageVersion, u64) = super::decode_state(state)?;
+			if version != 5 {
+				return Ok(())
+			}
+			ensure!(Pallet::<T>::on_chain_storage_version() == 6, "storage version not updated");
+			ensure!(Cycles::<T>::iter_values().count() as u64 == cycles, "cycles lost");
+			let scheduled: Vec<_> = CycleDeadlines::<T>::iter_values().flatten().collect();
+			for (cycle_id, cycle) in Cycles::<T>::iter() {
+				ensure!(
+					cycle.status.is_finalised() || scheduled.contains(&cycle_id),
+					"running cycle not scheduled"
+				);
+			}
+			Ok(())
+		}
+	}
+}
@@ -1,22 +1,33 @@
 use crate as pallet_rng_dao;
-use crate::pallet::Config;
+use crate::{
+	consumer::{CallbackId, RandomnessConsumer, RequestId},
+	funds::{CycleAccount, CycleFunds, NamedReserves},
+	pallet::{Config, CycleParameters},
+};
 use frame_support::{
+	dispatch::DispatchResult,
 	parameter_types,
-	traits::{ConstU16, ConstU32},
+	traits::{AsEnsureOriginWithArg, ConstU128, ConstU16, ConstU32, GenesisBuild},
+	weights::Weight,
 	PalletId,
 };
 use frame_system as system;
+use frame_system::{offchain::AppCrypto, EnsureRoot, EnsureSigned};
 use sp_core::H256;
 use sp_runtime::{
 	generic::Header,
-	traits::{BlakeTwo256, IdentityLookup},
+	testing::{TestSignature, TestXt, UintAuthorityId},
+	traits::{BlakeTwo256, Extrinsic as ExtrinsicT, IdentityLookup},
+	DispatchError, Perbill,
 };
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+pub(crate) type Extrinsic = TestXt<RuntimeCall, ()>;
 type Block = frame_system::mocking::MockBlock<Test>;
 type Balance = u128;
 type AccountId = u64;
 type CycleId = u128;
+type AssetId = u32;
 
 pub static ALICE: AccountId = 1;
 pub static BOB: AccountId = 2;
@@ -24,6 +35,10 @@ pub static CHARLIE: AccountId = 3;
 pub static EVE: AccountId = 4;
 pub static TOM: AccountId = 5;
 pub static BOT: AccountId = 6;
+pub static TREASURY: AccountId = 7;
+pub static ASSET: AssetId = 1;
+pub const LOTTERY: CallbackId = *b"lottery_";
+pub const RAFFLE: CallbackId = *b"raffle__";
 
 // Configure a mock runtime to test the pallet.
 frame_support::construct_runtime!(
@@ -33,8 +48,10 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system,
-		RngDao: pallet_rng_dao,
 		Balances: pallet_balances,
+		Assets: pallet_assets,
+		// after the pallets holding the bounties of its genesis cycles
+		RngDao: pallet_rng_dao,
 	}
 );
 
@@ -72,11 +89,30 @@ impl pallet_balances::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type ExistentialDeposit = ();
 	type MaxLocks = ();
-	type MaxReserves = ();
+	type MaxReserves = ConstU32<10>;
 	type ReserveIdentifier = [u8; 8];
 	type WeightInfo = ();
 }
 
+impl pallet_assets::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Balance = Balance;
+	type AssetId = AssetId;
+	type Currency = Balances;
+	type CreateOrigin = AsEnsureOriginWithArg<EnsureSigned<AccountId>>;
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type AssetDeposit = ConstU128<0>;
+	type AssetAccountDeposit = ConstU128<0>;
+	type MetadataDepositBase = ConstU128<0>;
+	type MetadataDepositPerByte = ConstU128<0>;
+	type ApprovalDeposit = ConstU128<0>;
+	type StringLimit = ConstU32<50>;
+	type Freezer = ();
+	type Extra = ();
+	type RemoveItemsLimit = ConstU32<1000>;
+	type WeightInfo = ();
+}
+
 parameter_types! {
 	pub MinBounty: u128 = 100_u128;
 	pub Deposit: u128 = 300_u128;
@@ -85,6 +121,39 @@ parameter_types! {
 	pub DelayBeforeSecondPhase: u32 = 2_u32;
 	pub SecondPhaseDuration: u32 = 5_u32;
 	pub MaxGenerators: u8 = 3_u8;
+	pub MinRevealers: u8 = 1_u8;
+	pub static CommitPhaseExtension: u32 = 0_u32;
+	pub static DepositRatio: Perbill = Perbill::zero();
+	pub MaxCommitPhaseExtensions: u8 = 2_u8;
+	pub TreasuryAccount: AccountId = TREASURY;
+	pub MaxCyclesPerBlock: u32 = 2_u32;
+	pub MaxFinalisationRetries: u32 = 2_u32;
+	pub MaxHistory: u32 = 2_u32;
+	pub RetentionPeriod: u32 = 10_u32;
+	pub MaxPendingRequests: u32 = 4_u32;
+	pub MaxRequestsPerConsumer: u32 = 3_u32;
+	pub MaxCallbackWeight: Weight = Weight::from_ref_time(100);
+	pub MaxContributors: u32 = 2_u32;
+	pub static LotteryWeight: Weight = Weight::from_ref_time(40);
+	pub static LotteryDraws: Vec<(RequestId, H256)> = vec![];
+	pub static RaffleDraws: Vec<(RequestId, H256)> = vec![];
+	pub static UseReserves: bool = false;
+	pub MinCycleParameters: CycleParameters<Balance, u32> = CycleParameters {
+		delay_before_bots: 1_u32,
+		delay_before_second_phase: 1_u32,
+		second_phase_duration: 1_u32,
+		max_generators: 1_u8,
+		deposit: 10_u128,
+		min_revealers: 1_u8,
+	};
+	pub MaxCycleParameters: CycleParameters<Balance, u32> = CycleParameters {
+		delay_before_bots: 10_u32,
+		delay_before_second_phase: 10_u32,
+		second_phase_duration: 10_u32,
+		max_generators: 5_u8,
+		deposit: 500_u128,
+		min_revealers: 5_u8,
+	};
 }
 
 impl Config for Test {
@@ -94,16 +163,182 @@ impl Config for Test {
 	type Deposit = Deposit;
 	type MinBounty = MinBounty;
 	type Balances = Balances;
+	type Funds = TestFunds;
+	type AssetId = AssetId;
+	type Assets = Assets;
+	type AssetOrigin = EnsureRoot<AccountId>;
 	type PalletId = TestPalletId;
 	type DelayBeforeBots = DelayBeforeBots;
 	type DelayBeforeSecondPhase = DelayBeforeSecondPhase;
 	type SecondPhaseDuration = SecondPhaseDuration;
 	type MaxGenerators = MaxGenerators;
+	type MinRevealers = MinRevealers;
+	type CommitPhaseExtension = CommitPhaseExtension;
+	type MaxCommitPhaseExtensions = MaxCommitPhaseExtensions;
+	type TreasuryAccount = TreasuryAccount;
+	type MaxCyclesPerBlock = MaxCyclesPerBlock;
+	type MaxFinalisationRetries = MaxFinalisationRetries;
+	type Combiner = crate::combiner::Xor;
+	type MaxHistory = MaxHistory;
+	type RetentionPeriod = RetentionPeriod;
+	type Consumers = (Lottery, Raffle);
+	type MaxPendingRequests = MaxPendingRequests;
+	type MaxRequestsPerConsumer = MaxRequestsPerConsumer;
+	type MaxCallbackWeight = MaxCallbackWeight;
+	type MaxContributors = MaxContributors;
+	type Withholding = crate::withholding::ScaledDeposit<DepositRatio>;
+	type BotOrigin = EnsureRoot<AccountId>;
+	type AdminOrigin = EnsureRoot<AccountId>;
+	type AuthorityId = TestAuthId;
+	type MinCycleParameters = MinCycleParameters;
+	type MaxCycleParameters = MaxCycleParameters;
 	type WeightInfo = ();
 }
 
+/// [`CycleAccount`], or [`NamedReserves`] when `UseReserves` is set.
+pub struct TestFunds;
+
+impl CycleFunds<Test> for TestFunds {
+	fn hold(cycle_id: &CycleId, who: &AccountId, amount: Balance) -> DispatchResult {
+		if UseReserves::get() {
+			<NamedReserves<Balances> as CycleFunds<Test>>::hold(cycle_id, who, amount)
+		} else {
+			<CycleAccount as CycleFunds<Test>>::hold(cycle_id, who, amount)
+		}
+	}
+
+	fn hold_for(
+		cycle_id: &CycleId,
+		who: &AccountId,
+		owner: &AccountId,
+		amount: Balance,
+	) -> DispatchResult {
+		if UseReserves::get() {
+			<NamedReserves<Balances> as CycleFunds<Test>>::hold_for(cycle_id, who, owner, amount)
+		} else {
+			<CycleAccount as CycleFunds<Test>>::hold_for(cycle_id, who, owner, amount)
+		}
+	}
+
+	fn pay(
+		cycle_id: &CycleId,
+		from: &AccountId,
+		to: &AccountId,
+		amount: Balance,
+	) -> DispatchResult {
+		if UseReserves::get() {
+			<NamedReserves<Balances> as CycleFunds<Test>>::pay(cycle_id, from, to, amount)
+		} else {
+			<CycleAccount as CycleFunds<Test>>::pay(cycle_id, from, to, amount)
+		}
+	}
+
+	fn sweep(
+		cycle_id: &CycleId,
+		from: &AccountId,
+		to: &AccountId,
+	) -> Result<Balance, DispatchError> {
+		if UseReserves::get() {
+			<NamedReserves<Balances> as CycleFunds<Test>>::sweep(cycle_id, from, to)
+		} else {
+			<CycleAccount as CycleFunds<Test>>::sweep(cycle_id, from, to)
+		}
+	}
+
+	fn held(cycle_id: &CycleId, holders: &[AccountId]) -> Balance {
+		if UseReserves::get() {
+			<NamedReserves<Balances> as CycleFunds<Test>>::held(cycle_id, holders)
+		} else {
+			<CycleAccount as CycleFunds<Test>>::held(cycle_id, holders)
+		}
+	}
+}
+
+/// Consumer going by `LOTTERY`, recording the random numbers it gets in `LotteryDraws`.
+pub struct Lottery;
+
+impl RandomnessConsumer for Lottery {
+	fn max_weight(callback: &CallbackId) -> Option<Weight> {
+		(*callback == LOTTERY).then(LotteryWeight::get)
+	}
+
+	fn on_randomness(
+		callback: &CallbackId,
+		request_id: RequestId,
+		random_number: H256,
+	) -> Option<Weight> {
+		if *callback != LOTTERY {
+			return None
+		}
+		LotteryDraws::mutate(|draws| draws.push((request_id, random_number)));
+		Some(LotteryWeight::get())
+	}
+}
+
+/// Consumer going by `RAFFLE`, recording the random numbers it gets in `RaffleDraws`.
+pub struct Raffle;
+
+impl RandomnessConsumer for Raffle {
+	fn max_weight(callback: &CallbackId) -> Option<Weight> {
+		(*callback == RAFFLE).then(|| Weight::from_ref_time(10))
+	}
+
+	fn on_randomness(
+		callback: &CallbackId,
+		request_id: RequestId,
+		random_number: H256,
+	) -> Option<Weight> {
+		if *callback != RAFFLE {
+			return None
+		}
+		RaffleDraws::mutate(|draws| draws.push((request_id, random_number)));
+		Some(Weight::from_ref_time(10))
+	}
+}
+
+pub struct TestAuthId;
+
+impl AppCrypto<UintAuthorityId, TestSignature> for TestAuthId {
+	type RuntimeAppPublic = UintAuthorityId;
+	type GenericPublic = UintAuthorityId;
+	type GenericSignature = TestSignature;
+}
+
+impl frame_system::offchain::SigningTypes for Test {
+	type Public = UintAuthorityId;
+	type Signature = TestSignature;
+}
+
+impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+	RuntimeCall: From<LocalCall>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = Extrinsic;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Test
+where
+	RuntimeCall: From<LocalCall>,
+{
+	fn create_transaction<C: AppCrypto<Self::Public, Self::Signature>>(
+		call: RuntimeCall,
+		_public: UintAuthorityId,
+		account: AccountId,
+		_nonce: u32,
+	) -> Option<(RuntimeCall, <Extrinsic as ExtrinsicT>::SignaturePayload)> {
+		Some((call, (account, ())))
+	}
+}
+
 pub struct ExtBuilder {
 	balances: Vec<(AccountId, Balance)>,
+	bots: Vec<AccountId>,
+	cycle_count: CycleId,
+	cycles: Vec<(AccountId, Balance, Option<CycleParameters<Balance, u32>>)>,
+	reserves: bool,
+	commit_extension: u32,
+	deposit_ratio: Perbill,
 }
 
 impl Default for ExtBuilder {
@@ -117,18 +352,83 @@ impl Default for ExtBuilder {
 				(TOM, 1_000),
 				(BOT, 1_000),
 			],
+			bots: vec![BOT],
+			cycle_count: 0,
+			cycles: vec![],
+			reserves: false,
+			commit_extension: 0,
+			deposit_ratio: Perbill::zero(),
 		}
 	}
 }
 
 impl ExtBuilder {
+	/// Keep funds of cycles in named reserves instead of cycle accounts.
+	pub fn with_reserves(mut self) -> Self {
+		self.reserves = true;
+		// reserved funds can only be repatriated to existing accounts
+		self.balances.push((TREASURY, 1));
+		self
+	}
+
+	/// Extend the commit phase of cycles by `blocks` while they lack generators.
+	pub fn with_commit_extension(mut self, blocks: u32) -> Self {
+		self.commit_extension = blocks;
+		self
+	}
+
+	/// Scale deposits of native bounties to `ratio` of the bounty.
+	pub fn with_deposit_ratio(mut self, ratio: Perbill) -> Self {
+		self.deposit_ratio = ratio;
+		self
+	}
+
+	/// Start cycles with ids from `cycle_count` on in the genesis block.
+	pub fn with_genesis_cycles(
+		mut self,
+		cycle_count: CycleId,
+		cycles: Vec<(AccountId, Balance, Option<CycleParameters<Balance, u32>>)>,
+	) -> Self {
+		self.cycle_count = cycle_count;
+		self.cycles = cycles;
+		self
+	}
+
 	pub fn build(self) -> sp_io::TestExternalities {
+		UseReserves::set(self.reserves);
+		CommitPhaseExtension::set(self.commit_extension);
+		DepositRatio::set(self.deposit_ratio);
 		let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
 
 		pallet_balances::GenesisConfig::<Test> { balances: self.balances }
 			.assimilate_storage(&mut t)
 			.unwrap();
 
+		pallet_assets::GenesisConfig::<Test> {
+			assets: vec![(ASSET, ALICE, true, 1)],
+			metadata: vec![],
+			accounts: vec![(ASSET, ALICE, 1_000), (ASSET, BOB, 1_000)],
+		}
+		.assimilate_storage(&mut t)
+		.unwrap();
+
+		pallet_rng_dao::GenesisConfig::<Test> {
+			cycle_count: self.cycle_count,
+			cycles: self.cycles,
+			bots: self.bots,
+		}
+		.assimilate_storage(&mut t)
+		.unwrap();
+
 		t.into()
 	}
+
+	/// Builds the externalities, runs `test` in them and checks the invariants of the pallet once
+	/// it is done.
+	pub fn build_and_execute(self, test: impl FnOnce()) {
+		self.build().execute_with(|| {
+			test();
+			RngDao::do_try_state().unwrap();
+		})
+	}
 }
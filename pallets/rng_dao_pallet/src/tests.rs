@@ -1,6 +1,50 @@
-use crate::{mock::*, Error, Event, RngCycle};
-use frame_support::{assert_noop, assert_ok};
-use sp_runtime::traits::{Hash, Keccak256};
+use crate::{
+	commitment::{commitment, legacy_commitment, CommitmentVersion, Salt, Secret},
+	funds::NamedReserves,
+	mock::*,
+	BotShare, CycleParameters, CycleStatus, Error, Event, Generator, GeneratorDetails, Parameters,
+	PredictedPayout, RngCycle,
+};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{Hooks, NamedReservableCurrency, OnRuntimeUpgrade},
+};
+use sp_core::H256;
+use sp_runtime::{
+	traits::{BadOrigin, Hash, Keccak256},
+	Perbill,
+};
+
+const SALT: Salt = [42_u8; 32];
+
+/// Commitment of `secret` sent by `who` to cycle `cycle_id`, salted with `SALT`.
+fn commit(cycle_id: u128, who: u64, secret: u64) -> H256 {
+	commitment(&cycle_id, &who, &SALT, &secret.to_le_bytes())
+}
+
+fn to_secret(secret: u64) -> Secret {
+	secret.to_le_bytes().to_vec().try_into().unwrap()
+}
+
+fn entropy(secret: u64) -> H256 {
+	CommitmentVersion::Salted.entropy(&secret.to_le_bytes())
+}
+
+/// Every generator of a cycle is either waiting to reveal or has revealed.
+fn assert_generators_count_consistent(cycle_id: u128) {
+	let generators = crate::Generators::<Test>::iter_prefix(cycle_id).count() +
+		crate::Reveals::<Test>::iter_prefix(cycle_id).count();
+	assert_eq!(RngDao::cycles(cycle_id).unwrap().generators_count as usize, generators);
+}
+
+/// Runs migration `M`, checked by its `pre_upgrade` and `post_upgrade` with `try-runtime`.
+fn upgrade<M: OnRuntimeUpgrade>() {
+	#[cfg(feature = "try-runtime")]
+	let state = M::pre_upgrade().unwrap();
+	M::on_runtime_upgrade();
+	#[cfg(feature = "try-runtime")]
+	M::post_upgrade(state).unwrap();
+}
 
 #[test]
 fn create_new_rng_cycle_works() {
@@ -8,7 +52,7 @@ fn create_new_rng_cycle_works() {
 		System::set_block_number(1);
 		let bounty = 150;
 		let free_balance = Balances::free_balance(ALICE);
-		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty));
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
 		assert_eq!(RngDao::get_cycle_count(), 1_u128);
 		assert_eq!(
 			RngDao::cycles(0_u128),
@@ -18,7 +62,10 @@ fn create_new_rng_cycle_works() {
 				started: 1,
 				generators_count: 0_u8,
 				revealed_count: 0_u8,
-				random_number: 0_u64,
+				random_number: H256::zero(),
+				status: CycleStatus::CommitPhase,
+				params: RngDao::default_cycle_parameters(),
+				commit_extensions: 0,
 			})
 		);
 		assert_eq!(Balances::free_balance(ALICE), free_balance - bounty);
@@ -31,7 +78,7 @@ fn create_new_rng_cycle_works() {
 fn create_new_rng_cycle_fails_due_to_low_bounty() {
 	ExtBuilder::default().build().execute_with(|| {
 		assert_noop!(
-			RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 50),
+			RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 50, None, None),
 			Error::<Test>::BountyMustBeGreaterThanMinBounty
 		);
 		assert_eq!(RngDao::get_cycle_count(), 0_u128);
@@ -44,7 +91,7 @@ fn basic_rng_works() {
 		System::set_block_number(1);
 		let bounty = 200;
 		let free_balance = Balances::free_balance(ALICE);
-		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty));
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
 		assert_eq!(RngDao::get_cycle_count(), 1_u128);
 		let cycle_id = 0_u128;
 		assert_eq!(
@@ -55,7 +102,10 @@ fn basic_rng_works() {
 				started: 1,
 				generators_count: 0_u8,
 				revealed_count: 0_u8,
-				random_number: 0_u64,
+				random_number: H256::zero(),
+				status: CycleStatus::CommitPhase,
+				params: RngDao::default_cycle_parameters(),
+				commit_extensions: 0,
 			})
 		);
 		assert_eq!(Balances::free_balance(ALICE), free_balance - bounty);
@@ -65,8 +115,8 @@ fn basic_rng_works() {
 		// BOB takes part
 		let bob_free_balance = Balances::free_balance(BOB);
 		let bob_secret = 9897_u64;
-		let bob_hash = Keccak256::hash(&bob_secret.to_le_bytes());
-		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash, false));
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
 		System::assert_last_event(
 			Event::HashReceived { cycle_id, sender: BOB, hash: bob_hash }.into(),
 		);
@@ -76,13 +126,8 @@ fn basic_rng_works() {
 		);
 		// CHARLIE takes part
 		let charlie_secret = 120019_u64;
-		let charlie_hash = Keccak256::hash(&charlie_secret.to_le_bytes());
-		assert_ok!(RngDao::send_hash(
-			RuntimeOrigin::signed(CHARLIE),
-			cycle_id,
-			charlie_hash,
-			false
-		));
+		let charlie_hash = commit(cycle_id, CHARLIE, charlie_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), cycle_id, charlie_hash));
 		System::assert_last_event(
 			Event::HashReceived { cycle_id, sender: CHARLIE, hash: charlie_hash }.into(),
 		);
@@ -91,25 +136,31 @@ fn basic_rng_works() {
 
 		// BOT takes part
 		let bot_secret = 807_u64;
-		let bot_hash = Keccak256::hash(&bot_secret.to_le_bytes());
-		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOT), cycle_id, bot_hash, true));
+		let bot_hash = commit(cycle_id, BOT, bot_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOT), cycle_id, bot_hash));
 		System::assert_last_event(
 			Event::HashReceived { cycle_id, sender: BOT, hash: bot_hash }.into(),
 		);
 
-		let expected_random_number = 0_u64 ^ bob_secret ^ charlie_secret ^ bot_secret;
+		let expected_random_number =
+			entropy(bob_secret) ^ entropy(charlie_secret) ^ entropy(bot_secret);
 
 		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 1);
 
 		// BOB reveals
-		assert_ok!(RngDao::reveal_secret(RuntimeOrigin::signed(BOB), cycle_id, bob_secret, false));
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			SALT
+		));
 		System::assert_last_event(Event::SecretReceived { cycle_id, sender: BOB }.into());
 		// CHARLIE reveals
 		assert_ok!(RngDao::reveal_secret(
 			RuntimeOrigin::signed(CHARLIE),
 			cycle_id,
-			charlie_secret,
-			false
+			to_secret(charlie_secret),
+			SALT
 		));
 		let cycle = RngDao::cycles(cycle_id).expect("Cycle not found");
 		// BOB get's his deposit back and he earns share from bounty too.
@@ -119,7 +170,12 @@ fn basic_rng_works() {
 		);
 		System::assert_last_event(Event::SecretReceived { cycle_id, sender: CHARLIE }.into());
 		// BOT reveals
-		assert_ok!(RngDao::reveal_secret(RuntimeOrigin::signed(BOT), cycle_id, bot_secret, true));
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOT),
+			cycle_id,
+			to_secret(bot_secret),
+			SALT
+		));
 		System::assert_last_event(Event::SecretReceived { cycle_id, sender: BOT }.into());
 
 		System::set_block_number(
@@ -146,6 +202,9 @@ fn basic_rng_works() {
 				generators_count: 3_u8,
 				revealed_count: 3_u8,
 				random_number: expected_random_number,
+				status: CycleStatus::Completed,
+				params: RngDao::default_cycle_parameters(),
+				commit_extensions: 0,
 			})
 		);
 	});
@@ -157,7 +216,7 @@ fn second_phase_not_started_yet_error() {
 		System::set_block_number(1);
 		let bounty = 150;
 		let free_balance = Balances::free_balance(ALICE);
-		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty));
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
 		assert_eq!(RngDao::get_cycle_count(), 1_u128);
 		let cycle_id = 0_u128;
 		assert_eq!(
@@ -168,18 +227,21 @@ fn second_phase_not_started_yet_error() {
 				started: 1,
 				generators_count: 0_u8,
 				revealed_count: 0_u8,
-				random_number: 0_u64,
+				random_number: H256::zero(),
+				status: CycleStatus::CommitPhase,
+				params: RngDao::default_cycle_parameters(),
+				commit_extensions: 0,
 			})
 		);
 		assert_eq!(Balances::free_balance(ALICE), free_balance - bounty);
 		assert_eq!(Balances::free_balance(RngDao::account_id(&cycle_id)), bounty);
 		System::assert_last_event(Event::CycleCreated { bounty, creator: ALICE }.into());
 		let bob_secret = 807_u64;
-		let bob_hash = Keccak256::hash(&bob_secret.to_le_bytes());
-		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash, false));
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
 		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 1);
 		assert_noop!(
-			RngDao::reveal_secret(RuntimeOrigin::signed(BOB), cycle_id, 10_u64, false),
+			RngDao::reveal_secret(RuntimeOrigin::signed(BOB), cycle_id, to_secret(10_u64), SALT),
 			Error::<Test>::SecretDoesNotMatchHash
 		);
 	});
@@ -191,7 +253,7 @@ fn not_submited_hash_in_first_phase_error() {
 		System::set_block_number(1);
 		let bounty = 150;
 		let free_balance = Balances::free_balance(ALICE);
-		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty));
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
 		assert_eq!(RngDao::get_cycle_count(), 1_u128);
 		let cycle_id = 0_u128;
 		assert_eq!(
@@ -202,18 +264,21 @@ fn not_submited_hash_in_first_phase_error() {
 				started: 1,
 				generators_count: 0_u8,
 				revealed_count: 0_u8,
-				random_number: 0_u64,
+				random_number: H256::zero(),
+				status: CycleStatus::CommitPhase,
+				params: RngDao::default_cycle_parameters(),
+				commit_extensions: 0,
 			})
 		);
 		assert_eq!(Balances::free_balance(ALICE), free_balance - bounty);
 		assert_eq!(Balances::free_balance(RngDao::account_id(&cycle_id)), bounty);
 		System::assert_last_event(Event::CycleCreated { bounty, creator: ALICE }.into());
 		let bob_secret = 807_u64;
-		let bob_hash = Keccak256::hash(&bob_secret.to_le_bytes());
-		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash, false));
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
 		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 1);
 		assert_noop!(
-			RngDao::reveal_secret(RuntimeOrigin::signed(EVE), cycle_id, 10_u64, false),
+			RngDao::reveal_secret(RuntimeOrigin::signed(EVE), cycle_id, to_secret(10_u64), SALT),
 			Error::<Test>::NotSubmitedHashInFirstPhase
 		);
 	});
@@ -225,7 +290,7 @@ fn random_number_not_yet_generated_error() {
 		System::set_block_number(1);
 		let bounty = 150;
 		let free_balance = Balances::free_balance(ALICE);
-		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty));
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
 		assert_eq!(RngDao::get_cycle_count(), 1_u128);
 		let cycle_id = 0_u128;
 		assert_eq!(
@@ -236,17 +301,25 @@ fn random_number_not_yet_generated_error() {
 				started: 1,
 				generators_count: 0_u8,
 				revealed_count: 0_u8,
-				random_number: 0_u64,
+				random_number: H256::zero(),
+				status: CycleStatus::CommitPhase,
+				params: RngDao::default_cycle_parameters(),
+				commit_extensions: 0,
 			})
 		);
 		assert_eq!(Balances::free_balance(ALICE), free_balance - bounty);
 		assert_eq!(Balances::free_balance(RngDao::account_id(&cycle_id)), bounty);
 		System::assert_last_event(Event::CycleCreated { bounty, creator: ALICE }.into());
 		let bob_secret = 807_u64;
-		let bob_hash = Keccak256::hash(&bob_secret.to_le_bytes());
-		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash, false));
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
 		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 1);
-		assert_ok!(RngDao::reveal_secret(RuntimeOrigin::signed(BOB), cycle_id, bob_secret, false));
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			SALT
+		));
 		assert_noop!(
 			RngDao::get_random_number(RuntimeOrigin::signed(ALICE), 0_u128),
 			Error::<Test>::RandomNumberNotYetGenerated
@@ -260,7 +333,7 @@ fn return_bounty_case1() {
 		System::set_block_number(1);
 		let bounty = 150;
 		let free_balance = Balances::free_balance(ALICE);
-		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty));
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
 		assert_eq!(RngDao::get_cycle_count(), 1_u128);
 		let cycle_id = 0_u128;
 		assert_eq!(
@@ -271,7 +344,10 @@ fn return_bounty_case1() {
 				started: 1,
 				generators_count: 0_u8,
 				revealed_count: 0_u8,
-				random_number: 0_u64,
+				random_number: H256::zero(),
+				status: CycleStatus::CommitPhase,
+				params: RngDao::default_cycle_parameters(),
+				commit_extensions: 0,
 			})
 		);
 		assert_eq!(Balances::free_balance(ALICE), free_balance - bounty);
@@ -295,7 +371,7 @@ fn return_bounty_case2() {
 		System::set_block_number(1);
 		let bounty = 150;
 		let free_balance = Balances::free_balance(ALICE);
-		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty));
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
 		assert_eq!(RngDao::get_cycle_count(), 1_u128);
 		let cycle_id = 0_u128;
 		assert_eq!(
@@ -306,15 +382,18 @@ fn return_bounty_case2() {
 				started: 1,
 				generators_count: 0_u8,
 				revealed_count: 0_u8,
-				random_number: 0_u64,
+				random_number: H256::zero(),
+				status: CycleStatus::CommitPhase,
+				params: RngDao::default_cycle_parameters(),
+				commit_extensions: 0,
 			})
 		);
 		assert_eq!(Balances::free_balance(ALICE), free_balance - bounty);
 		assert_eq!(Balances::free_balance(RngDao::account_id(&cycle_id)), bounty);
 		System::assert_last_event(Event::CycleCreated { bounty, creator: ALICE }.into());
 		let bob_secret = 807_u64;
-		let bob_hash = Keccak256::hash(&bob_secret.to_le_bytes());
-		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash, false));
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
 		// no generators revealed correct secret in time
 		System::set_block_number(
 			1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 5
@@ -333,7 +412,7 @@ fn not_authorized_to_get_random_number_error() {
 		System::set_block_number(1);
 		let bounty = 150;
 		let free_balance = Balances::free_balance(ALICE);
-		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty));
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
 		assert_eq!(RngDao::get_cycle_count(), 1_u128);
 		let cycle_id = 0_u128;
 		assert_eq!(
@@ -344,17 +423,25 @@ fn not_authorized_to_get_random_number_error() {
 				started: 1,
 				generators_count: 0_u8,
 				revealed_count: 0_u8,
-				random_number: 0_u64,
+				random_number: H256::zero(),
+				status: CycleStatus::CommitPhase,
+				params: RngDao::default_cycle_parameters(),
+				commit_extensions: 0,
 			})
 		);
 		assert_eq!(Balances::free_balance(ALICE), free_balance - bounty);
 		assert_eq!(Balances::free_balance(RngDao::account_id(&cycle_id)), bounty);
 		System::assert_last_event(Event::CycleCreated { bounty, creator: ALICE }.into());
 		let bob_secret = 807_u64;
-		let bob_hash = Keccak256::hash(&bob_secret.to_le_bytes());
-		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash, false));
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
 		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 1);
-		assert_ok!(RngDao::reveal_secret(RuntimeOrigin::signed(BOB), cycle_id, bob_secret, false));
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			SALT
+		));
 		System::set_block_number(
 			1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 5
 			/*SecondPhaseDuration*/ + 1,
@@ -372,7 +459,7 @@ fn secret_does_not_match_hash_error() {
 		System::set_block_number(1);
 		let bounty = 150;
 		let free_balance = Balances::free_balance(ALICE);
-		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty));
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
 		assert_eq!(RngDao::get_cycle_count(), 1_u128);
 		let cycle_id = 0_u128;
 		assert_eq!(
@@ -383,17 +470,25 @@ fn secret_does_not_match_hash_error() {
 				started: 1,
 				generators_count: 0_u8,
 				revealed_count: 0_u8,
-				random_number: 0_u64,
+				random_number: H256::zero(),
+				status: CycleStatus::CommitPhase,
+				params: RngDao::default_cycle_parameters(),
+				commit_extensions: 0,
 			})
 		);
 		assert_eq!(Balances::free_balance(ALICE), free_balance - bounty);
 		assert_eq!(Balances::free_balance(RngDao::account_id(&cycle_id)), bounty);
 		System::assert_last_event(Event::CycleCreated { bounty, creator: ALICE }.into());
 		let bob_secret = 807_u64;
-		let bob_hash = Keccak256::hash(&bob_secret.to_le_bytes());
-		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash, false));
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
 		assert_noop!(
-			RngDao::reveal_secret(RuntimeOrigin::signed(BOB), cycle_id, bob_secret, false),
+			RngDao::reveal_secret(
+				RuntimeOrigin::signed(BOB),
+				cycle_id,
+				to_secret(bob_secret),
+				SALT
+			),
 			Error::<Test>::SecondPhaseNotStartedYet
 		);
 	});
@@ -405,7 +500,7 @@ fn bots_cannot_participate_before_delay() {
 		System::set_block_number(1);
 		let bounty = 150;
 		let free_balance = Balances::free_balance(ALICE);
-		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty));
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
 		assert_eq!(RngDao::get_cycle_count(), 1_u128);
 		let cycle_id = 0_u128;
 		assert_eq!(
@@ -416,16 +511,19 @@ fn bots_cannot_participate_before_delay() {
 				started: 1,
 				generators_count: 0_u8,
 				revealed_count: 0_u8,
-				random_number: 0_u64,
+				random_number: H256::zero(),
+				status: CycleStatus::CommitPhase,
+				params: RngDao::default_cycle_parameters(),
+				commit_extensions: 0,
 			})
 		);
 		assert_eq!(Balances::free_balance(ALICE), free_balance - bounty);
 		assert_eq!(Balances::free_balance(RngDao::account_id(&cycle_id)), bounty);
 		System::assert_last_event(Event::CycleCreated { bounty, creator: ALICE }.into());
 		let bot_secret = 807_u64;
-		let bot_hash = Keccak256::hash(&bot_secret.to_le_bytes());
+		let bot_hash = commit(cycle_id, BOT, bot_secret);
 		assert_noop!(
-			RngDao::send_hash(RuntimeOrigin::signed(BOT), cycle_id, bot_hash, true),
+			RngDao::send_hash(RuntimeOrigin::signed(BOT), cycle_id, bot_hash),
 			Error::<Test>::BotsNotAllowedYet
 		);
 	});
@@ -437,7 +535,7 @@ fn max_generators_error() {
 		System::set_block_number(1);
 		let bounty = 150;
 		let free_balance = Balances::free_balance(ALICE);
-		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty));
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
 		assert_eq!(RngDao::get_cycle_count(), 1_u128);
 		let cycle_id = 0_u128;
 		assert_eq!(
@@ -448,7 +546,10 @@ fn max_generators_error() {
 				started: 1,
 				generators_count: 0_u8,
 				revealed_count: 0_u8,
-				random_number: 0_u64,
+				random_number: H256::zero(),
+				status: CycleStatus::CommitPhase,
+				params: RngDao::default_cycle_parameters(),
+				commit_extensions: 0,
 			})
 		);
 		assert_eq!(Balances::free_balance(ALICE), free_balance - bounty);
@@ -456,12 +557,2457 @@ fn max_generators_error() {
 		System::assert_last_event(Event::CycleCreated { bounty, creator: ALICE }.into());
 		let secret = 807_u64;
 		let hash = Keccak256::hash(&secret.to_le_bytes());
-		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, hash, false));
-		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), cycle_id, hash, false));
-		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(EVE), cycle_id, hash, false));
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, hash));
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), cycle_id, hash));
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(EVE), cycle_id, hash));
 		assert_noop!(
-			RngDao::send_hash(RuntimeOrigin::signed(TOM), cycle_id, hash, false),
+			RngDao::send_hash(RuntimeOrigin::signed(TOM), cycle_id, hash),
 			Error::<Test>::MaxGeneratorsReached
 		);
 	});
 }
+
+#[test]
+fn cycle_status_follows_deadlines() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		let bob_secret = 807_u64;
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::CommitPhase);
+
+		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 1);
+		let bot_hash = commit(cycle_id, BOT, 10_u64);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOT), cycle_id, bot_hash));
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::BotCommitPhase);
+
+		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/);
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			SALT
+		));
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::RevealPhase);
+
+		System::set_block_number(RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap()));
+		assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::Completed);
+	});
+}
+
+#[test]
+fn get_random_number_only_once() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		let bob_secret = 807_u64;
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 1);
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			SALT
+		));
+		System::set_block_number(
+			1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 5
+			/*SecondPhaseDuration*/ + 1,
+		);
+		assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+		assert_noop!(
+			RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id),
+			Error::<Test>::CycleAlreadyFinalised
+		);
+		assert_noop!(
+			RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), cycle_id, bob_hash),
+			Error::<Test>::CycleAlreadyFinalised
+		);
+	});
+}
+
+#[test]
+fn failed_cycle_refunds_bounty_only_once() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		let cycle_id = 0_u128;
+		let free_balance = Balances::free_balance(ALICE);
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		// ALICE funds another cycle so that the pallet would have enough to refund twice
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		System::set_block_number(
+			1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 5
+			/*SecondPhaseDuration*/ + 1,
+		);
+		assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::Failed);
+		assert_eq!(Balances::free_balance(ALICE), free_balance - bounty);
+		assert_noop!(
+			RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id),
+			Error::<Test>::CycleAlreadyFinalised
+		);
+	});
+}
+
+#[test]
+fn migrate_to_v2_sets_cycle_status_and_params() {
+	use crate::migrations::{
+		v1::{MigrateToV1, OldRngCycle},
+		v2::{self, MigrateToV2},
+	};
+	use frame_support::{
+		storage::unhashed,
+		traits::{GetStorageVersion, StorageVersion},
+	};
+
+	ExtBuilder::default().build().execute_with(|| {
+		StorageVersion::new(0).put::<RngDao>();
+		let old_cycle = |started, revealed_count| OldRngCycle {
+			creator: ALICE,
+			bounty: 150_u128,
+			started,
+			random_number: 0_u64,
+			generators_count: 1_u8,
+			revealed_count,
+		};
+		// completed before the upgrade
+		unhashed::put(&crate::Cycles::<Test>::hashed_key_for(0_u128), &old_cycle(1_u32, 1_u8));
+		// failed and already refunded before the upgrade
+		unhashed::put(&crate::Cycles::<Test>::hashed_key_for(1_u128), &old_cycle(1_u32, 0_u8));
+		// deadlines passed but not yet finalised
+		unhashed::put(&crate::Cycles::<Test>::hashed_key_for(2_u128), &old_cycle(1_u32, 0_u8));
+		assert_ok!(Balances::transfer(
+			RuntimeOrigin::signed(ALICE),
+			RngDao::account_id(&2_u128),
+			150
+		));
+		// still in first phase
+		unhashed::put(&crate::Cycles::<Test>::hashed_key_for(3_u128), &old_cycle(18_u32, 0_u8));
+
+		System::set_block_number(20);
+		upgrade::<MigrateToV1<Test>>();
+		upgrade::<MigrateToV2<Test>>();
+
+		assert_eq!(RngDao::on_chain_storage_version(), 2);
+		let cycle = |cycle_id| v2::Cycles::<Test>::get(cycle_id).unwrap();
+		assert_eq!(cycle(0_u128).params, v2::default_cycle_parameters::<Test>());
+		assert_eq!(cycle(0_u128).status, CycleStatus::Completed);
+		assert_eq!(cycle(1_u128).status, CycleStatus::Failed);
+		assert_eq!(cycle(2_u128).status, CycleStatus::Finished);
+		assert_eq!(cycle(3_u128).status, CycleStatus::CommitPhase);
+	});
+}
+
+#[test]
+fn send_hash_respects_commit_phase_boundaries() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		let hash = Keccak256::hash(&807_u64.to_le_bytes());
+
+		// last block in which only humans can commit
+		System::set_block_number(1 + 3 /*DelayBeforeBots*/);
+		assert_noop!(
+			RngDao::send_hash(RuntimeOrigin::signed(BOT), cycle_id, hash),
+			Error::<Test>::BotsNotAllowedYet
+		);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, hash));
+
+		// last block of the commit phase
+		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ - 1);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOT), cycle_id, hash));
+
+		// first block of the reveal phase
+		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/);
+		assert_noop!(
+			RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), cycle_id, hash),
+			Error::<Test>::CommitPhaseOver
+		);
+
+		// after the cycle deadline
+		System::set_block_number(RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap()));
+		assert_noop!(
+			RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), cycle_id, hash),
+			Error::<Test>::CommitPhaseOver
+		);
+	});
+}
+
+#[test]
+fn reveal_secret_respects_reveal_phase_boundaries() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		let bob_secret = 807_u64;
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		let charlie_secret = 120019_u64;
+		let charlie_hash = commit(cycle_id, CHARLIE, charlie_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), cycle_id, charlie_hash));
+		let eve_secret = 42_u64;
+		let eve_hash = commit(cycle_id, EVE, eve_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(EVE), cycle_id, eve_hash));
+
+		// last block of the commit phase
+		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ - 1);
+		assert_noop!(
+			RngDao::reveal_secret(
+				RuntimeOrigin::signed(BOB),
+				cycle_id,
+				to_secret(bob_secret),
+				SALT
+			),
+			Error::<Test>::SecondPhaseNotStartedYet
+		);
+
+		// first block of the reveal phase
+		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/);
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			SALT
+		));
+
+		// last block of the reveal phase
+		System::set_block_number(
+			1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 5
+			/*SecondPhaseDuration*/ - 1,
+		);
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(CHARLIE),
+			cycle_id,
+			to_secret(charlie_secret),
+			SALT
+		));
+
+		// first block after the reveal phase
+		System::set_block_number(RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap()));
+		assert_noop!(
+			RngDao::reveal_secret(
+				RuntimeOrigin::signed(EVE),
+				cycle_id,
+				to_secret(eve_secret),
+				SALT
+			),
+			Error::<Test>::RevealPhaseOver
+		);
+	});
+}
+
+#[test]
+fn unrevealed_deposit_and_leftover_go_to_treasury() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		let cycle_id = 0_u128;
+		let deposit = <Test as crate::Config>::Deposit::get();
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		let bob_secret = 807_u64;
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		let charlie_hash = commit(cycle_id, CHARLIE, 120019_u64);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), cycle_id, charlie_hash));
+		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 1);
+		// only BOB reveals, CHARLIE looses deposit
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			SALT
+		));
+		System::set_block_number(RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap()));
+		assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+
+		// 2 generators + our profit share
+		let share = bounty / 3;
+		System::assert_has_event(
+			Event::CycleSettled { cycle_id, slashed: deposit, leftover: bounty - share }.into(),
+		);
+		assert_eq!(Balances::free_balance(TREASURY), deposit + bounty - share);
+		assert_eq!(Balances::free_balance(RngDao::account_id(&cycle_id)), 0);
+	});
+}
+
+#[test]
+fn failed_cycle_slashes_deposits_to_treasury() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		let cycle_id = 0_u128;
+		let deposit = <Test as crate::Config>::Deposit::get();
+		let free_balance = Balances::free_balance(ALICE);
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		let bob_hash = commit(cycle_id, BOB, 807_u64);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		System::set_block_number(RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap()));
+		assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+
+		System::assert_has_event(
+			Event::CycleSettled { cycle_id, slashed: deposit, leftover: 0 }.into(),
+		);
+		System::assert_last_event(Event::CycleFailed { cycle_id, creator: ALICE }.into());
+		assert_eq!(Balances::free_balance(ALICE), free_balance);
+		assert_eq!(Balances::free_balance(TREASURY), deposit);
+		assert_eq!(Balances::free_balance(RngDao::account_id(&cycle_id)), 0);
+	});
+}
+
+#[test]
+fn on_initialize_finalises_cycle_at_deadline() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		let deadline = RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap());
+		assert_eq!(RngDao::cycle_deadlines(deadline).into_inner(), vec![cycle_id]);
+		let bob_secret = 807_u64;
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 1);
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			SALT
+		));
+
+		System::set_block_number(deadline);
+		RngDao::on_initialize(deadline);
+		System::assert_last_event(
+			Event::CycleCompleted { cycle_id, creator: ALICE, random_number: entropy(bob_secret) }
+				.into(),
+		);
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::Completed);
+		assert!(RngDao::cycle_deadlines(deadline).is_empty());
+		// manual call is only a fallback
+		assert_noop!(
+			RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id),
+			Error::<Test>::CycleAlreadyFinalised
+		);
+	});
+}
+
+#[test]
+fn on_initialize_refunds_failed_cycle() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		let cycle_id = 0_u128;
+		let free_balance = Balances::free_balance(ALICE);
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		let deadline = RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap());
+
+		System::set_block_number(deadline);
+		RngDao::on_initialize(deadline);
+		System::assert_last_event(Event::CycleFailed { cycle_id, creator: ALICE }.into());
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::Failed);
+		assert_eq!(Balances::free_balance(ALICE), free_balance);
+	});
+}
+
+#[test]
+fn cycles_failing_to_finalise_are_retried() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		let deadline = RngDao::cycle_deadline(&RngDao::cycles(0_u128).unwrap());
+		// the block after the deadline is full
+		System::set_block_number(2);
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(BOB), bounty, None, None));
+		assert_ok!(RngDao::create_new_rng_cycle(
+			RuntimeOrigin::signed(CHARLIE),
+			bounty,
+			None,
+			None
+		));
+
+		// bounty can not be refunded
+		Balances::make_free_balance_be(&RngDao::account_id(&0_u128), 0);
+		System::set_block_number(deadline);
+		RngDao::on_initialize(deadline);
+		assert!(!RngDao::cycles(0_u128).unwrap().status.is_finalised());
+		assert!(System::events().iter().any(|record| matches!(
+			record.event,
+			RuntimeEvent::RngDao(Event::CycleFinalisationFailed { cycle_id: 0, .. })
+		)));
+		assert_eq!(RngDao::cycle_deadlines(deadline + 2).to_vec(), vec![0_u128]);
+
+		assert_eq!(RngDao::finalisation_retries(0_u128), 1);
+
+		Balances::make_free_balance_be(&RngDao::account_id(&0_u128), bounty);
+		System::set_block_number(deadline + 2);
+		RngDao::on_initialize(deadline + 2);
+		assert_eq!(RngDao::cycles(0_u128).unwrap().status, CycleStatus::Failed);
+		System::assert_has_event(Event::CycleFailed { cycle_id: 0, creator: ALICE }.into());
+		assert!(!crate::FinalisationRetries::<Test>::contains_key(0_u128));
+	});
+}
+
+#[test]
+fn cycles_failing_to_finalise_too_often_are_left_to_their_creator() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		let deadline = RngDao::cycle_deadline(&RngDao::cycles(0_u128).unwrap());
+
+		// bounty can not be refunded, the cycle is tried at its deadline and retried twice
+		Balances::make_free_balance_be(&RngDao::account_id(&0_u128), 0);
+		for block in deadline..=deadline + 2 {
+			System::set_block_number(block);
+			RngDao::on_initialize(block);
+		}
+		System::assert_last_event(Event::CycleFinalisationAbandoned { cycle_id: 0 }.into());
+		assert!(RngDao::cycle_deadlines(deadline + 3).is_empty());
+		assert!(!crate::FinalisationRetries::<Test>::contains_key(0_u128));
+		assert!(!RngDao::cycles(0_u128).unwrap().status.is_finalised());
+
+		Balances::make_free_balance_be(&RngDao::account_id(&0_u128), bounty);
+		assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), 0));
+		assert_eq!(RngDao::cycles(0_u128).unwrap().status, CycleStatus::Failed);
+	});
+}
+
+#[test]
+fn cycles_at_a_full_deadline_are_finalised_later() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(BOB), bounty, None, None));
+		let deadline = RngDao::cycle_deadline(&RngDao::cycles(0_u128).unwrap());
+		// deadline block is full, the cycle is still created
+		assert_ok!(RngDao::create_new_rng_cycle(
+			RuntimeOrigin::signed(CHARLIE),
+			bounty,
+			None,
+			None
+		));
+		assert_eq!(RngDao::cycle_deadline(&RngDao::cycles(2_u128).unwrap()), deadline);
+		assert_eq!(RngDao::cycle_deadlines(deadline).to_vec(), vec![0_u128, 1]);
+		assert_eq!(RngDao::cycle_deadlines(deadline + 1).to_vec(), vec![2_u128]);
+
+		System::set_block_number(deadline);
+		RngDao::on_initialize(deadline);
+		assert!(!RngDao::cycles(2_u128).unwrap().status.is_finalised());
+		System::set_block_number(deadline + 1);
+		RngDao::on_initialize(deadline + 1);
+		assert_eq!(RngDao::cycles(2_u128).unwrap().status, CycleStatus::Failed);
+		assert_eq!(Balances::free_balance(CHARLIE), 1_000);
+	});
+}
+
+#[test]
+fn randomness_uses_latest_completed_cycle() {
+	use frame_support::traits::Randomness;
+
+	ExtBuilder::default().build().execute_with(|| {
+		assert_eq!(
+			RngDao::random(b"lottery"),
+			(Keccak256::hash_of(&(&b"lottery"[..], H256::zero())), 0)
+		);
+
+		System::set_block_number(1);
+		let bounty = 150;
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		let bob_secret = 807_u64;
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 1);
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			SALT
+		));
+		let deadline = RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap());
+		System::set_block_number(deadline);
+		RngDao::on_initialize(deadline);
+
+		let (lottery, known_since) = RngDao::random(b"lottery");
+		assert_eq!(lottery, Keccak256::hash_of(&(&b"lottery"[..], entropy(bob_secret))));
+		assert_eq!(known_since, deadline);
+		assert_ne!(RngDao::random(b"nft").0, lottery);
+	});
+}
+
+#[test]
+fn runtime_api_queries_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		let cycle_id = 0_u128;
+		assert_eq!(RngDao::cycle_details(cycle_id), None);
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		let bob_secret = 807_u64;
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		let charlie_hash = commit(cycle_id, CHARLIE, 120019_u64);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), cycle_id, charlie_hash));
+
+		let details = RngDao::cycle_details(cycle_id).unwrap();
+		assert_eq!(details.phase, CycleStatus::CommitPhase);
+		assert_eq!(
+			details.second_phase_start,
+			1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/
+		);
+		assert_eq!(details.deadline, details.second_phase_start + 5 /*SecondPhaseDuration*/);
+		assert_eq!(RngDao::open_commitments(BOB), vec![cycle_id]);
+		assert_eq!(RngDao::open_commitments(EVE), Vec::<u128>::new());
+		// 2 generators + our profit share
+		let deposit = <Test as crate::Config>::Deposit::get();
+		let payout = bounty / 3 + deposit;
+		assert_eq!(
+			RngDao::predicted_payout(cycle_id, BOB),
+			Some(PredictedPayout { deposit, share: bounty / 3, asset: None })
+		);
+		assert_eq!(RngDao::predicted_payout(cycle_id, EVE), None);
+
+		System::set_block_number(details.second_phase_start);
+		let bob_free_balance = Balances::free_balance(BOB);
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			SALT
+		));
+		assert_eq!(Balances::free_balance(BOB), bob_free_balance + payout);
+		assert_eq!(RngDao::reveals(cycle_id, BOB), Some(to_secret(bob_secret)));
+		assert_eq!(RngDao::cycle_details(cycle_id).unwrap().phase, CycleStatus::RevealPhase);
+		assert_eq!(RngDao::open_commitments(BOB), Vec::<u128>::new());
+		assert_eq!(RngDao::predicted_payout(cycle_id, BOB), None);
+		let mut generators = RngDao::cycle_generators(cycle_id);
+		generators.sort_by_key(|generator| generator.account);
+		assert_eq!(
+			generators,
+			vec![
+				GeneratorDetails { account: BOB, revealed: true },
+				GeneratorDetails { account: CHARLIE, revealed: false },
+			]
+		);
+	});
+}
+
+#[test]
+fn cycle_with_custom_parameters_works() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		let cycle_id = 0_u128;
+		let params = CycleParameters {
+			delay_before_bots: 1_u32,
+			delay_before_second_phase: 1_u32,
+			second_phase_duration: 2_u32,
+			max_generators: 1_u8,
+			deposit: 50_u128,
+			min_revealers: 1_u8,
+		};
+		assert_ok!(RngDao::create_new_rng_cycle(
+			RuntimeOrigin::signed(ALICE),
+			bounty,
+			Some(params.clone()),
+			None
+		));
+		let cycle = RngDao::cycles(cycle_id).unwrap();
+		assert_eq!(cycle.params, params);
+		assert_eq!(RngDao::cycle_deadline(&cycle), 1 + 1 + 1 + 2);
+
+		let bob_free_balance = Balances::free_balance(BOB);
+		let bob_secret = 807_u64;
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		assert_eq!(Balances::free_balance(BOB), bob_free_balance - params.deposit);
+		assert_noop!(
+			RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), cycle_id, bob_hash),
+			Error::<Test>::MaxGeneratorsReached
+		);
+
+		System::set_block_number(RngDao::second_phase_start(&cycle));
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			SALT
+		));
+		// 1 generator + our profit share
+		assert_eq!(Balances::free_balance(BOB), bob_free_balance + bounty / 2);
+
+		System::set_block_number(RngDao::cycle_deadline(&cycle));
+		assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::Completed);
+	});
+}
+
+#[test]
+fn invalid_cycle_parameters_error() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let params = CycleParameters {
+			delay_before_bots: 1_u32,
+			delay_before_second_phase: 1_u32,
+			second_phase_duration: 2_u32,
+			max_generators: 1_u8,
+			deposit: 50_u128,
+			min_revealers: 1_u8,
+		};
+		let out_of_bounds = [
+			CycleParameters { delay_before_bots: 11_u32, ..params.clone() },
+			CycleParameters { delay_before_second_phase: 0_u32, ..params.clone() },
+			CycleParameters { second_phase_duration: 0_u32, ..params.clone() },
+			CycleParameters { max_generators: 6_u8, ..params.clone() },
+			CycleParameters { deposit: 5_u128, ..params.clone() },
+			CycleParameters { min_revealers: 0_u8, ..params.clone() },
+			// more reveals than generators
+			CycleParameters { min_revealers: 2_u8, ..params.clone() },
+		];
+		for params in out_of_bounds {
+			assert_noop!(
+				RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, Some(params), None),
+				Error::<Test>::InvalidCycleParameters
+			);
+		}
+	});
+}
+
+#[test]
+fn copied_commitment_cannot_be_revealed() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		let bob_secret = 807_u64;
+		let bob_hash = commit(0_u128, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), 0_u128, bob_hash));
+		// CHARLIE copies BOB's hash in the same cycle and BOB's hash in another cycle
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), 0_u128, bob_hash));
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), 1_u128, bob_hash));
+
+		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 1);
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			0_u128,
+			to_secret(bob_secret),
+			SALT
+		));
+		// BOB's revealed secret and salt are now public, still no one can reuse them
+		assert_noop!(
+			RngDao::reveal_secret(
+				RuntimeOrigin::signed(CHARLIE),
+				0_u128,
+				to_secret(bob_secret),
+				SALT
+			),
+			Error::<Test>::SecretDoesNotMatchHash
+		);
+		assert_noop!(
+			RngDao::reveal_secret(RuntimeOrigin::signed(BOB), 1_u128, to_secret(bob_secret), SALT),
+			Error::<Test>::SecretDoesNotMatchHash
+		);
+	});
+}
+
+#[test]
+fn secrets_of_any_length_up_to_max_can_be_revealed() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		let short: Secret = vec![7_u8].try_into().unwrap();
+		let long: Secret =
+			vec![7_u8; crate::commitment::MAX_SECRET_LEN as usize].try_into().unwrap();
+		let bob_hash = commitment(&cycle_id, &BOB, &SALT, &short);
+		let charlie_salt = [1_u8; 32];
+		let charlie_hash = commitment(&cycle_id, &CHARLIE, &charlie_salt, &long);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), cycle_id, charlie_hash));
+
+		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 1);
+		// salt is part of the commitment
+		assert_noop!(
+			RngDao::reveal_secret(RuntimeOrigin::signed(CHARLIE), cycle_id, long.clone(), SALT),
+			Error::<Test>::SecretDoesNotMatchHash
+		);
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			short.clone(),
+			SALT
+		));
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(CHARLIE),
+			cycle_id,
+			long.clone(),
+			charlie_salt
+		));
+		assert_eq!(
+			RngDao::revealed_entropies(cycle_id).to_vec(),
+			vec![
+				CommitmentVersion::Salted.entropy(&short),
+				CommitmentVersion::Salted.entropy(&long)
+			]
+		);
+		assert_eq!(RngDao::reveals(cycle_id, CHARLIE), Some(long));
+	});
+}
+
+#[test]
+fn migrate_to_v3_keeps_legacy_commitments_revealable() {
+	use crate::migrations::v3::{MigrateToV3, OldGenerator};
+	use frame_support::{
+		storage::unhashed,
+		traits::{GetStorageVersion, StorageVersion},
+	};
+
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		let bob_secret = 807_u64;
+		let bob_hash = legacy_commitment(bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), cycle_id, bob_hash));
+		// as stored by storage version 2, CHARLIE already revealed
+		StorageVersion::new(2).put::<RngDao>();
+		unhashed::put(
+			&crate::Generators::<Test>::hashed_key_for(cycle_id, BOB),
+			&OldGenerator { secret: 0_u64, hash: bob_hash, is_bot: false },
+		);
+		crate::Generators::<Test>::remove(cycle_id, CHARLIE);
+		unhashed::put(&crate::Reveals::<Test>::hashed_key_for(cycle_id, CHARLIE), &120019_u64);
+
+		upgrade::<MigrateToV3<Test>>();
+
+		assert_eq!(RngDao::on_chain_storage_version(), 3);
+		assert_eq!(
+			RngDao::generators(cycle_id, BOB),
+			Some(Generator { hash: bob_hash, is_bot: false, version: CommitmentVersion::Legacy })
+		);
+		assert_eq!(RngDao::reveals(cycle_id, CHARLIE), Some(to_secret(120019_u64)));
+
+		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 1);
+		// legacy commitments are not salted
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			[0_u8; 32]
+		));
+		assert_eq!(
+			RngDao::revealed_entropies(cycle_id).to_vec(),
+			vec![CommitmentVersion::Legacy.entropy(&bob_secret.to_le_bytes())]
+		);
+	});
+}
+
+#[test]
+fn combiners_combine_entropies_in_reveal_order() {
+	use crate::combiner::{Combiner, KeccakChain, KeccakMerkleRoot, Xor};
+
+	let (a, b, c) = (entropy(9897), entropy(120019), entropy(807));
+	assert_eq!(Xor::combine(&[a, b, c]), a ^ b ^ c);
+	assert_eq!(Xor::combine(&[c, b, a]), Xor::combine(&[a, b, c]));
+
+	let chain = Keccak256::hash_of(&(Keccak256::hash_of(&(H256::zero(), a)), b));
+	assert_eq!(KeccakChain::combine(&[a, b]), chain);
+	assert_ne!(KeccakChain::combine(&[b, a]), chain);
+
+	let root = Keccak256::hash_of(&(Keccak256::hash_of(&(a, b)), c));
+	assert_eq!(KeccakMerkleRoot::combine(&[a, b, c]), Keccak256::hash(root.as_bytes()));
+	assert_eq!(KeccakMerkleRoot::combine(&[a]), Keccak256::hash(a.as_bytes()));
+}
+
+#[test]
+fn migrate_to_v4_widens_random_numbers() {
+	use crate::migrations::{
+		v2,
+		v4::{self, MigrateToV4},
+	};
+	use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+	ExtBuilder::default().build().execute_with(|| {
+		StorageVersion::new(3).put::<RngDao>();
+		let old_cycle = |status, random_number| v2::RngCycle {
+			creator: ALICE,
+			bounty: 150_u128,
+			started: 1_u32,
+			random_number,
+			generators_count: 2_u8,
+			revealed_count: 1_u8,
+			status,
+			params: v2::default_cycle_parameters::<Test>(),
+		};
+		v2::Cycles::<Test>::insert(0_u128, old_cycle(CycleStatus::Completed, 807_u64));
+		v2::Cycles::<Test>::insert(1_u128, old_cycle(CycleStatus::RevealPhase, 9897_u64));
+		v4::LatestRandomNumber::<Test>::put((807_u64, 11_u32));
+
+		upgrade::<MigrateToV4<Test>>();
+
+		let widened =
+			|random_number: u64| CommitmentVersion::Legacy.entropy(&random_number.to_le_bytes());
+		assert_eq!(RngDao::on_chain_storage_version(), 4);
+		let cycle = |cycle_id| v4::Cycles::<Test>::get(cycle_id).unwrap();
+		assert_eq!(cycle(0_u128).random_number, widened(807));
+		assert!(RngDao::revealed_entropies(0_u128).is_empty());
+		// random number of a running cycle is only known once it completes
+		assert_eq!(cycle(1_u128).random_number, H256::zero());
+		assert_eq!(RngDao::revealed_entropies(1_u128).to_vec(), vec![widened(9897)]);
+		assert_eq!(RngDao::latest_random_number(), Some((widened(807), 11)));
+	});
+}
+
+#[test]
+fn migrate_to_v5_keeps_single_revealer_cycles() {
+	use crate::migrations::{v2, v4, v5::MigrateToV5};
+	use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+	ExtBuilder::default().build().execute_with(|| {
+		StorageVersion::new(4).put::<RngDao>();
+		let old_params = v2::default_cycle_parameters::<Test>();
+		v4::Cycles::<Test>::insert(
+			0_u128,
+			v2::RngCycle {
+				creator: ALICE,
+				bounty: 150_u128,
+				started: 1_u32,
+				random_number: H256::zero(),
+				generators_count: 2_u8,
+				revealed_count: 1_u8,
+				status: CycleStatus::RevealPhase,
+				params: old_params.clone(),
+			},
+		);
+
+		upgrade::<MigrateToV5<Test>>();
+
+		assert_eq!(RngDao::on_chain_storage_version(), 5);
+		let cycle = RngDao::cycles(0_u128).unwrap();
+		assert_eq!(cycle.params.max_generators, old_params.max_generators);
+		assert_eq!(cycle.params.deposit, old_params.deposit);
+		assert_eq!(cycle.params.min_revealers, 1);
+		assert_eq!(cycle.commit_extensions, 0);
+		assert_eq!(cycle.status, CycleStatus::RevealPhase);
+	});
+}
+
+#[test]
+fn migrate_to_v6_schedules_running_cycles() {
+	use crate::migrations::v6::MigrateToV6;
+	use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		let short = CycleParameters {
+			delay_before_bots: 1,
+			delay_before_second_phase: 1,
+			second_phase_duration: 1,
+			max_generators: 3,
+			deposit: 300,
+			min_revealers: 1,
+		};
+		assert_ok!(RngDao::create_new_rng_cycle(
+			RuntimeOrigin::signed(BOB),
+			150,
+			Some(short),
+			None
+		));
+		let hash = commit(0, CHARLIE, 807);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), 0, hash));
+		let cycle = RngDao::cycles(0_u128).unwrap();
+		let deadline = RngDao::cycle_deadline(&cycle);
+		// as stored before cycles were finalised at their deadline
+		StorageVersion::new(5).put::<RngDao>();
+		let _ = crate::CycleDeadlines::<Test>::clear(u32::MAX, None);
+
+		// deadline of the cycle of BOB has passed
+		let now = RngDao::second_phase_start(&cycle);
+		System::set_block_number(now);
+		upgrade::<MigrateToV6<Test>>();
+		assert_eq!(RngDao::on_chain_storage_version(), 6);
+		assert_eq!(RngDao::cycle_deadlines(now).to_vec(), vec![1_u128]);
+		assert_eq!(RngDao::cycle_deadlines(deadline).to_vec(), vec![0_u128]);
+
+		RngDao::on_initialize(now);
+		assert_eq!(RngDao::cycles(1_u128).unwrap().status, CycleStatus::Failed);
+		assert_eq!(Balances::free_balance(BOB), 1_000);
+		assert_ok!(RngDao::reveal_secret(RuntimeOrigin::signed(CHARLIE), 0, to_secret(807), SALT));
+		System::set_block_number(deadline);
+		RngDao::on_initialize(deadline);
+		let cycle = RngDao::cycles(0_u128).unwrap();
+		assert_eq!(cycle.status, CycleStatus::Completed);
+		assert_eq!(cycle.random_number, entropy(807));
+	});
+}
+
+#[test]
+fn migrations_upgrade_version_0_storage_to_current_version() {
+	use crate::migrations::{
+		v1::{MigrateToV1, OldRngCycle},
+		v2::MigrateToV2,
+		v3::{MigrateToV3, OldGenerator},
+		v4::{self, MigrateToV4},
+		v5::MigrateToV5,
+		v6::MigrateToV6,
+	};
+	use frame_support::{
+		storage::unhashed,
+		traits::{GetStorageVersion, StorageVersion},
+	};
+
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		let bob_secret = 807_u64;
+		let bob_hash = legacy_commitment(bob_secret);
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), cycle_id, bob_hash));
+		// as stored by storage version 0, CHARLIE already revealed and is not a generator anymore
+		StorageVersion::new(0).put::<RngDao>();
+		unhashed::put(
+			&crate::Cycles::<Test>::hashed_key_for(cycle_id),
+			&OldRngCycle {
+				creator: ALICE,
+				bounty: 150_u128,
+				started: 1_u32,
+				random_number: 9897_u64,
+				generators_count: 2_u8,
+				revealed_count: 1_u8,
+			},
+		);
+		unhashed::put(
+			&crate::Generators::<Test>::hashed_key_for(cycle_id, BOB),
+			&OldGenerator { secret: 0_u64, hash: bob_hash, is_bot: false },
+		);
+		crate::Generators::<Test>::remove(cycle_id, CHARLIE);
+		v4::LatestRandomNumber::<Test>::put((807_u64, 11_u32));
+
+		upgrade::<MigrateToV1<Test>>();
+		upgrade::<MigrateToV2<Test>>();
+		upgrade::<MigrateToV3<Test>>();
+		upgrade::<MigrateToV4<Test>>();
+		upgrade::<MigrateToV5<Test>>();
+		upgrade::<MigrateToV6<Test>>();
+
+		assert_eq!(RngDao::on_chain_storage_version(), 6);
+		let widened =
+			|random_number: u64| CommitmentVersion::Legacy.entropy(&random_number.to_le_bytes());
+		assert_eq!(RngDao::latest_random_number(), Some((widened(807), 11)));
+		let cycle = RngDao::cycles(cycle_id).unwrap();
+		assert_eq!(cycle.status, CycleStatus::CommitPhase);
+		assert_eq!(cycle.params.min_revealers, 1);
+
+		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 1);
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			[0_u8; 32]
+		));
+		System::set_block_number(
+			1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 5
+			/*SecondPhaseDuration*/ + 1,
+		);
+		assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+		let cycle = RngDao::cycles(cycle_id).unwrap();
+		assert_eq!(cycle.status, CycleStatus::Completed);
+		assert_eq!(cycle.revealed_count, 2);
+		assert_eq!(cycle.random_number, widened(9897) ^ widened(bob_secret));
+	});
+}
+
+#[test]
+fn bot_registry_is_managed_by_bot_origin() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_noop!(RngDao::register_bot(RuntimeOrigin::signed(ALICE), TOM), BadOrigin);
+		assert_ok!(RngDao::register_bot(RuntimeOrigin::root(), TOM));
+		System::assert_last_event(Event::BotRegistered { bot: TOM }.into());
+		assert!(RngDao::is_bot(&TOM));
+		assert_noop!(
+			RngDao::register_bot(RuntimeOrigin::root(), TOM),
+			Error::<Test>::BotAlreadyRegistered
+		);
+
+		assert_noop!(RngDao::deregister_bot(RuntimeOrigin::signed(TOM), TOM), BadOrigin);
+		assert_ok!(RngDao::deregister_bot(RuntimeOrigin::root(), TOM));
+		System::assert_last_event(Event::BotDeregistered { bot: TOM }.into());
+		assert!(!RngDao::is_bot(&TOM));
+		assert_noop!(RngDao::deregister_bot(RuntimeOrigin::root(), TOM), Error::<Test>::NotABot);
+
+		assert_noop!(
+			RngDao::set_bot_share_policy(RuntimeOrigin::signed(ALICE), BotShare::Flat(10)),
+			BadOrigin
+		);
+		assert_ok!(RngDao::set_bot_share_policy(RuntimeOrigin::root(), BotShare::Flat(10)));
+		System::assert_last_event(Event::BotSharePolicySet { policy: BotShare::Flat(10) }.into());
+		assert_eq!(RngDao::bot_share_policy(), BotShare::Flat(10));
+	});
+}
+
+#[test]
+fn bot_status_comes_from_registry() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		// TOM is not registered, so he commits as a human
+		assert_ok!(RngDao::send_hash(
+			RuntimeOrigin::signed(TOM),
+			cycle_id,
+			commit(cycle_id, TOM, 1)
+		));
+		assert!(!RngDao::generators(cycle_id, TOM).unwrap().is_bot);
+
+		assert_ok!(RngDao::register_bot(RuntimeOrigin::root(), EVE));
+		assert_noop!(
+			RngDao::send_hash(RuntimeOrigin::signed(EVE), cycle_id, commit(cycle_id, EVE, 2)),
+			Error::<Test>::BotsNotAllowedYet
+		);
+		System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 1);
+		assert_ok!(RngDao::send_hash(
+			RuntimeOrigin::signed(EVE),
+			cycle_id,
+			commit(cycle_id, EVE, 2)
+		));
+		assert!(RngDao::generators(cycle_id, EVE).unwrap().is_bot);
+	});
+}
+
+#[test]
+fn bots_are_paid_according_to_bot_share_policy() {
+	let reveal_with_policy = |policy: BotShare<u128>| {
+		let mut bot_profit = 0;
+		ExtBuilder::default().build().execute_with(|| {
+			System::set_block_number(1);
+			let cycle_id = 0_u128;
+			let bounty = 150;
+			assert_ok!(RngDao::set_bot_share_policy(RuntimeOrigin::root(), policy));
+			assert_ok!(RngDao::create_new_rng_cycle(
+				RuntimeOrigin::signed(ALICE),
+				bounty,
+				None,
+				None
+			));
+			assert_ok!(RngDao::send_hash(
+				RuntimeOrigin::signed(BOB),
+				cycle_id,
+				commit(cycle_id, BOB, 9897)
+			));
+			System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 1);
+			assert_ok!(RngDao::send_hash(
+				RuntimeOrigin::signed(BOT),
+				cycle_id,
+				commit(cycle_id, BOT, 807)
+			));
+
+			System::set_block_number(1 + 3 /*DelayBeforeBots*/ + 2 /*DelayBeforeSecondPhase*/ + 1);
+			let bob_free_balance = Balances::free_balance(BOB);
+			let bot_free_balance = Balances::free_balance(BOT);
+			let deposit = <Test as crate::Config>::Deposit::get();
+			assert_eq!(
+				RngDao::predicted_payout(cycle_id, BOB),
+				Some(PredictedPayout { deposit, share: bounty / 3, asset: None })
+			);
+			let predicted = RngDao::predicted_payout(cycle_id, BOT).unwrap();
+			assert_eq!(predicted.deposit, deposit);
+			assert_ok!(RngDao::reveal_secret(
+				RuntimeOrigin::signed(BOB),
+				cycle_id,
+				to_secret(9897),
+				SALT
+			));
+			assert_ok!(RngDao::reveal_secret(
+				RuntimeOrigin::signed(BOT),
+				cycle_id,
+				to_secret(807),
+				SALT
+			));
+			// humans always get an equal share
+			assert_eq!(Balances::free_balance(BOB), bob_free_balance + deposit + bounty / 3);
+			assert_eq!(Balances::free_balance(BOT), bot_free_balance + deposit + predicted.share);
+			bot_profit = predicted.share;
+		});
+		bot_profit
+	};
+
+	assert_eq!(reveal_with_policy(BotShare::Equal), 50);
+	assert_eq!(reveal_with_policy(BotShare::Reduced(Perbill::from_percent(50))), 25);
+	assert_eq!(reveal_with_policy(BotShare::Flat(10)), 10);
+	// flat fee is capped by the share of humans
+	assert_eq!(reveal_with_policy(BotShare::Flat(100)), 50);
+}
+
+#[test]
+fn offchain_worker_takes_part_as_bot() {
+	use codec::Decode;
+	use sp_core::offchain::{
+		testing::{TestOffchainExt, TestTransactionPoolExt},
+		OffchainDbExt, OffchainWorkerExt, StorageKind, TransactionPoolExt,
+	};
+	use sp_runtime::{testing::UintAuthorityId, traits::Dispatchable};
+
+	let mut ext = ExtBuilder::default().build();
+	let (offchain, _) = TestOffchainExt::new();
+	let (pool, pool_state) = TestTransactionPoolExt::new();
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+	UintAuthorityId::set_all_keys(vec![BOT]);
+
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		let next_transaction = || {
+			let tx = pool_state.write().transactions.pop()?;
+			Some(Extrinsic::decode(&mut &*tx).unwrap())
+		};
+
+		// only humans can commit in the next block
+		RngDao::offchain_worker(3);
+		assert!(next_transaction().is_none());
+
+		RngDao::offchain_worker(4);
+		let tx = next_transaction().unwrap();
+		assert_eq!(tx.signature, Some((BOT, ())));
+		System::set_block_number(5);
+		assert_ok!(tx.call.dispatch(RuntimeOrigin::signed(BOT)));
+		assert!(RngDao::generators(cycle_id, BOT).unwrap().is_bot);
+		// commits only once to a cycle
+		RngDao::offchain_worker(4);
+		assert!(next_transaction().is_none());
+
+		// reveal phase starts in the next block
+		RngDao::offchain_worker(5);
+		let tx = next_transaction().unwrap();
+		assert_eq!(tx.signature, Some((BOT, ())));
+		System::set_block_number(6);
+		assert_ok!(tx.call.dispatch(RuntimeOrigin::signed(BOT)));
+		System::assert_last_event(Event::SecretReceived { cycle_id, sender: BOT }.into());
+
+		// secret is forgotten once it is revealed
+		let key = RngDao::bot_commitment_key(&cycle_id);
+		assert!(sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &key).is_some());
+		RngDao::offchain_worker(6);
+		assert!(next_transaction().is_none());
+		assert!(sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &key).is_none());
+	});
+}
+
+#[test]
+fn offchain_worker_commits_only_to_cycles_short_of_revealers() {
+	use codec::Decode;
+	use sp_core::offchain::{
+		testing::{TestOffchainExt, TestTransactionPoolExt},
+		OffchainDbExt, OffchainWorkerExt, TransactionPoolExt,
+	};
+	use sp_runtime::testing::UintAuthorityId;
+
+	let mut ext = ExtBuilder::default().build();
+	let (offchain, _) = TestOffchainExt::new();
+	let (pool, pool_state) = TestTransactionPoolExt::new();
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+	UintAuthorityId::set_all_keys(vec![BOT]);
+
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		// bots can commit to all of them in the same blocks, deadlines differ as only two cycles
+		// fit in a block
+		let params = RngDao::default_cycle_parameters();
+		for (second_phase_duration, min_revealers) in
+			[(5, 1), (5, 1), (6, 1), (6, 1), (7, 2), (7, 1)]
+		{
+			let params = CycleParameters { second_phase_duration, min_revealers, ..params.clone() };
+			assert_ok!(RngDao::create_new_rng_cycle(
+				RuntimeOrigin::signed(ALICE),
+				150,
+				Some(params),
+				None
+			));
+		}
+		// the first cycle has all the generators it needs
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), 0, commit(0, BOB, 807)));
+
+		RngDao::offchain_worker(4);
+		let committed: Vec<u128> = pool_state
+			.write()
+			.transactions
+			.drain(..)
+			.map(|tx| match Extrinsic::decode(&mut &*tx).unwrap().call {
+				RuntimeCall::RngDao(crate::Call::send_hash { cycle_id, .. }) => cycle_id,
+				call => panic!("unexpected call {:?}", call),
+			})
+			.collect();
+		// the cycle needing two reveals first, then the ones closest to their deadline, at most
+		// `MAX_OFFCHAIN_COMMITS` of them
+		assert_eq!(committed, vec![4, 1, 2, 3]);
+	});
+}
+
+#[test]
+fn second_commitment_to_a_cycle_is_rejected() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		assert_generators_count_consistent(cycle_id);
+		let hash = commit(cycle_id, BOB, 1);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, hash));
+		assert_generators_count_consistent(cycle_id);
+
+		let free_balance = Balances::free_balance(BOB);
+		assert_noop!(
+			RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, commit(cycle_id, BOB, 2)),
+			Error::<Test>::AlreadyCommitted
+		);
+		// the same hash again is rejected as well
+		assert_noop!(
+			RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, hash),
+			Error::<Test>::AlreadyCommitted
+		);
+		assert_eq!(Balances::free_balance(BOB), free_balance);
+		assert_eq!(RngDao::generators(cycle_id, BOB).unwrap().hash, hash);
+
+		assert_ok!(RngDao::send_hash(
+			RuntimeOrigin::signed(CHARLIE),
+			cycle_id,
+			commit(cycle_id, CHARLIE, 3)
+		));
+		assert_generators_count_consistent(cycle_id);
+
+		System::set_block_number(RngDao::second_phase_start(&RngDao::cycles(cycle_id).unwrap()));
+		assert_ok!(RngDao::reveal_secret(RuntimeOrigin::signed(BOB), cycle_id, to_secret(1), SALT));
+		assert_generators_count_consistent(cycle_id);
+
+		System::set_block_number(11);
+		assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+		assert_generators_count_consistent(cycle_id);
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().generators_count, 2);
+	});
+}
+
+#[test]
+fn named_reserves_keep_funds_on_their_owners_accounts() {
+	ExtBuilder::default().with_reserves().build_and_execute(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		let cycle_id = 0_u128;
+		let deposit = <Test as crate::Config>::Deposit::get();
+		let reserve_id = NamedReserves::<Balances>::reserve_id::<Test>(&cycle_id);
+		let treasury_balance = Balances::free_balance(TREASURY);
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		assert_eq!(Balances::reserved_balance_named(&reserve_id, &ALICE), bounty);
+		let bob_secret = 807_u64;
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		let charlie_hash = commit(cycle_id, CHARLIE, 120019_u64);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), cycle_id, charlie_hash));
+		assert_eq!(Balances::reserved_balance_named(&reserve_id, &BOB), deposit);
+		assert_eq!(Balances::reserved_balance_named(&reserve_id, &CHARLIE), deposit);
+		// no account is created for the cycle
+		assert!(!System::account_exists(&RngDao::account_id(&cycle_id)));
+
+		System::set_block_number(RngDao::second_phase_start(&RngDao::cycles(cycle_id).unwrap()));
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			SALT
+		));
+		// 2 generators + our profit share
+		let share = bounty / 3;
+		assert_eq!(Balances::reserved_balance(BOB), 0);
+		assert_eq!(Balances::free_balance(BOB), 1_000 + share);
+		assert_eq!(Balances::reserved_balance_named(&reserve_id, &ALICE), bounty - share);
+
+		System::set_block_number(RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap()));
+		assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+		System::assert_has_event(
+			Event::CycleSettled { cycle_id, slashed: deposit, leftover: bounty - share }.into(),
+		);
+		assert_eq!(Balances::free_balance(TREASURY), treasury_balance + deposit + bounty - share);
+		assert_eq!(Balances::reserved_balance(ALICE), 0);
+		assert_eq!(Balances::free_balance(ALICE), 1_000 - bounty);
+		assert_eq!(Balances::reserved_balance(CHARLIE), 0);
+		assert_eq!(Balances::free_balance(CHARLIE), 1_000 - deposit);
+	});
+}
+
+#[test]
+fn named_reserves_are_released_when_cycle_fails() {
+	ExtBuilder::default().with_reserves().build().execute_with(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		assert_eq!(Balances::reserved_balance(ALICE), 150);
+		System::set_block_number(RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap()));
+		assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+
+		System::assert_last_event(Event::CycleFailed { cycle_id, creator: ALICE }.into());
+		assert_eq!(Balances::reserved_balance(ALICE), 0);
+		assert_eq!(Balances::free_balance(ALICE), 1_000);
+	});
+}
+
+#[test]
+fn bounties_can_be_posted_in_accepted_assets() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+		assert_noop!(
+			RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, Some(ASSET)),
+			Error::<Test>::AssetNotAccepted
+		);
+		assert_noop!(
+			RngDao::set_min_asset_bounty(RuntimeOrigin::signed(ALICE), ASSET, Some(120)),
+			BadOrigin
+		);
+		assert_ok!(RngDao::set_min_asset_bounty(RuntimeOrigin::root(), ASSET, Some(120)));
+		System::assert_last_event(
+			Event::MinAssetBountySet { asset: ASSET, min_bounty: Some(120) }.into(),
+		);
+		assert_noop!(
+			RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 100, None, Some(ASSET)),
+			Error::<Test>::BountyMustBeGreaterThanMinBounty
+		);
+
+		let bounty = 150;
+		let cycle_id = 0_u128;
+		let deposit = <Test as crate::Config>::Deposit::get();
+		let native_balance = Balances::free_balance(ALICE);
+		assert_ok!(RngDao::create_new_rng_cycle(
+			RuntimeOrigin::signed(ALICE),
+			bounty,
+			None,
+			Some(ASSET)
+		));
+		assert_eq!(RngDao::bounty_asset(cycle_id), Some(ASSET));
+		assert_eq!(Assets::balance(ASSET, ALICE), 1_000 - bounty);
+		assert_eq!(Assets::balance(ASSET, RngDao::account_id(&cycle_id)), bounty);
+		assert_eq!(Balances::free_balance(ALICE), native_balance);
+
+		let bob_secret = 807_u64;
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		let charlie_hash = commit(cycle_id, CHARLIE, 120019_u64);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), cycle_id, charlie_hash));
+		// deposits stay in the native token
+		assert_eq!(Balances::free_balance(BOB), 1_000 - deposit);
+		assert_eq!(Balances::free_balance(RngDao::account_id(&cycle_id)), 2 * deposit);
+
+		// 2 generators + our profit share
+		let share = bounty / 3;
+		assert_eq!(
+			RngDao::predicted_payout(cycle_id, BOB),
+			Some(PredictedPayout { deposit, share, asset: Some(ASSET) })
+		);
+
+		System::set_block_number(RngDao::second_phase_start(&RngDao::cycles(cycle_id).unwrap()));
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			SALT
+		));
+		assert_eq!(Balances::free_balance(BOB), 1_000);
+		assert_eq!(Assets::balance(ASSET, BOB), 1_000 + share);
+		assert_ok!(RngDao::do_try_state());
+
+		System::set_block_number(RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap()));
+		assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+		System::assert_has_event(
+			Event::CycleSettled { cycle_id, slashed: deposit, leftover: bounty - share }.into(),
+		);
+		assert_eq!(Balances::free_balance(TREASURY), deposit);
+		assert_eq!(Assets::balance(ASSET, TREASURY), bounty - share);
+		assert_eq!(Assets::balance(ASSET, RngDao::account_id(&cycle_id)), 0);
+	});
+}
+
+#[test]
+fn asset_dust_the_treasury_can_not_hold_stays_with_the_cycle() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::set_min_asset_bounty(RuntimeOrigin::root(), ASSET, Some(100)));
+		assert_ok!(RngDao::create_new_rng_cycle(
+			RuntimeOrigin::signed(ALICE),
+			150,
+			None,
+			Some(ASSET)
+		));
+		let bob_secret = 807_u64;
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		System::set_block_number(RngDao::second_phase_start(&RngDao::cycles(cycle_id).unwrap()));
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			SALT
+		));
+		// 1 generator + our profit share
+		let leftover = 150 - 150 / 2;
+		assert_eq!(Assets::balance(ASSET, RngDao::account_id(&cycle_id)), leftover);
+		// treasury holds none of the asset and can not receive less than its minimum balance
+		assert_ok!(Assets::force_asset_status(
+			RuntimeOrigin::root(),
+			ASSET,
+			ALICE,
+			ALICE,
+			ALICE,
+			ALICE,
+			leftover + 1,
+			false,
+			false
+		));
+
+		System::set_block_number(RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap()));
+		assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::Completed);
+		assert_eq!(Assets::balance(ASSET, TREASURY), 0);
+		assert_eq!(Assets::balance(ASSET, RngDao::account_id(&cycle_id)), leftover);
+	});
+}
+
+#[test]
+fn bounty_can_be_posted_in_an_asset_which_is_not_sufficient() {
+	for builder in [ExtBuilder::default(), ExtBuilder::default().with_reserves()] {
+		builder.build_and_execute(|| {
+			System::set_block_number(1);
+			let cycle_id = 0_u128;
+			let asset = ASSET + 1;
+			assert_ok!(Assets::force_create(RuntimeOrigin::root(), asset, ALICE, false, 1));
+			assert_ok!(Assets::mint(RuntimeOrigin::signed(ALICE), asset, ALICE, 1_000));
+			assert_ok!(RngDao::set_min_asset_bounty(RuntimeOrigin::root(), asset, Some(100)));
+			assert_ok!(RngDao::create_new_rng_cycle(
+				RuntimeOrigin::signed(ALICE),
+				150,
+				None,
+				Some(asset)
+			));
+			let cycle_account = RngDao::account_id(&cycle_id);
+			assert_eq!(Assets::balance(asset, cycle_account), 150);
+
+			let bob_secret = 807_u64;
+			let bob_hash = commit(cycle_id, BOB, bob_secret);
+			assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+			System::set_block_number(RngDao::second_phase_start(
+				&RngDao::cycles(cycle_id).unwrap(),
+			));
+			assert_ok!(RngDao::reveal_secret(
+				RuntimeOrigin::signed(BOB),
+				cycle_id,
+				to_secret(bob_secret),
+				SALT
+			));
+			assert_eq!(Assets::balance(asset, BOB), 150 / 2);
+
+			System::set_block_number(RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap()));
+			assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+			assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::Completed);
+			assert_eq!(
+				Assets::balance(asset, TREASURY) + Assets::balance(asset, cycle_account),
+				150 - 150 / 2
+			);
+		});
+	}
+}
+
+#[test]
+fn asset_bounty_is_refunded_when_cycle_fails() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::set_min_asset_bounty(RuntimeOrigin::root(), ASSET, Some(100)));
+		assert_ok!(RngDao::create_new_rng_cycle(
+			RuntimeOrigin::signed(ALICE),
+			150,
+			None,
+			Some(ASSET)
+		));
+		// stopping to accept the asset does not affect running cycles
+		assert_ok!(RngDao::set_min_asset_bounty(RuntimeOrigin::root(), ASSET, None));
+		System::set_block_number(RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap()));
+		assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+
+		System::assert_last_event(Event::CycleFailed { cycle_id, creator: ALICE }.into());
+		assert_eq!(Assets::balance(ASSET, ALICE), 1_000);
+		assert_eq!(Assets::balance(ASSET, RngDao::account_id(&cycle_id)), 0);
+	});
+}
+
+#[test]
+fn creator_can_cancel_cycle_nobody_joined() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		assert_noop!(
+			RngDao::cancel_cycle(RuntimeOrigin::signed(BOB), cycle_id),
+			Error::<Test>::NotAuthorizedToCancelCycle
+		);
+		assert_ok!(RngDao::cancel_cycle(RuntimeOrigin::signed(ALICE), cycle_id));
+		System::assert_last_event(Event::CycleCancelled { cycle_id, creator: ALICE }.into());
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::Cancelled);
+		assert_eq!(Balances::free_balance(ALICE), 1_000);
+		assert_eq!(Balances::free_balance(RngDao::account_id(&cycle_id)), 0);
+
+		assert_noop!(
+			RngDao::cancel_cycle(RuntimeOrigin::signed(ALICE), cycle_id),
+			Error::<Test>::CycleAlreadyFinalised
+		);
+		let bob_hash = commit(cycle_id, BOB, 807_u64);
+		assert_noop!(
+			RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash),
+			Error::<Test>::CycleAlreadyFinalised
+		);
+		// nothing is left to finalise at the deadline
+		let deadline = RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap());
+		assert!(crate::CycleDeadlines::<Test>::get(deadline).is_empty());
+	});
+}
+
+#[test]
+fn cycle_cannot_be_cancelled_after_first_commitment() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		let bob_hash = commit(cycle_id, BOB, 807_u64);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		assert_noop!(
+			RngDao::cancel_cycle(RuntimeOrigin::signed(ALICE), cycle_id),
+			Error::<Test>::CycleAlreadyJoined
+		);
+	});
+}
+
+#[test]
+fn top_ups_raise_shares_of_generators() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		let top_up = 60;
+		let cycle_id = 0_u128;
+		let deposit = <Test as crate::Config>::Deposit::get();
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), bounty, None, None));
+		let bob_secret = 807_u64;
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		assert_ok!(RngDao::top_up_bounty(RuntimeOrigin::signed(EVE), cycle_id, top_up));
+		System::assert_last_event(
+			Event::BountyToppedUp { cycle_id, sender: EVE, amount: top_up }.into(),
+		);
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().bounty, bounty + top_up);
+		assert_eq!(Balances::free_balance(EVE), 1_000 - top_up);
+		// 1 generator + our profit share
+		let share = (bounty + top_up) / 2;
+		assert_eq!(
+			RngDao::predicted_payout(cycle_id, BOB),
+			Some(PredictedPayout { deposit, share, asset: None })
+		);
+
+		System::set_block_number(RngDao::second_phase_start(&RngDao::cycles(cycle_id).unwrap()));
+		// shares must not change once reveals may have been paid
+		assert_noop!(
+			RngDao::top_up_bounty(RuntimeOrigin::signed(EVE), cycle_id, top_up),
+			Error::<Test>::CommitPhaseOver
+		);
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			SALT
+		));
+		assert_eq!(Balances::free_balance(BOB), 1_000 + share);
+	});
+}
+
+#[test]
+fn top_ups_are_refunded_to_contributors_when_cycle_is_cancelled() {
+	for builder in [ExtBuilder::default(), ExtBuilder::default().with_reserves()] {
+		builder.build_and_execute(|| {
+			System::set_block_number(1);
+			let cycle_id = 0_u128;
+			assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+			assert_ok!(RngDao::top_up_bounty(RuntimeOrigin::signed(BOB), cycle_id, 60));
+			assert_ok!(RngDao::top_up_bounty(RuntimeOrigin::signed(BOB), cycle_id, 40));
+			// top-ups of the creator are part of her bounty
+			assert_ok!(RngDao::top_up_bounty(RuntimeOrigin::signed(ALICE), cycle_id, 30));
+			assert_eq!(RngDao::bounty_contributions(cycle_id).into_inner(), vec![(BOB, 100)]);
+			assert_eq!(RngDao::cycles(cycle_id).unwrap().bounty, 150 + 100 + 30);
+			assert_eq!(Balances::free_balance(BOB), 1_000 - 100);
+
+			assert_ok!(RngDao::cancel_cycle(RuntimeOrigin::signed(ALICE), cycle_id));
+			assert_eq!(Balances::free_balance(ALICE), 1_000);
+			assert_eq!(Balances::free_balance(BOB), 1_000);
+			assert_eq!(Balances::reserved_balance(ALICE), 0);
+			assert!(RngDao::bounty_contributions(cycle_id).is_empty());
+		});
+	}
+}
+
+#[test]
+fn top_ups_are_refunded_to_contributors_when_cycle_fails() {
+	ExtBuilder::default().with_reserves().build_and_execute(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		let reserve_id = NamedReserves::<Balances>::reserve_id::<Test>(&cycle_id);
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		assert_ok!(RngDao::top_up_bounty(RuntimeOrigin::signed(EVE), cycle_id, 60));
+		// top-ups are held in the creator's reserve along with the rest of the bounty
+		assert_eq!(Balances::reserved_balance(EVE), 0);
+		assert_eq!(Balances::free_balance(EVE), 1_000 - 60);
+		assert_eq!(Balances::reserved_balance_named(&reserve_id, &ALICE), 150 + 60);
+
+		let deadline = RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap());
+		System::set_block_number(deadline);
+		RngDao::on_initialize(deadline);
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::Failed);
+		assert_eq!(Balances::reserved_balance(ALICE), 0);
+		assert_eq!(Balances::free_balance(ALICE), 1_000);
+		assert_eq!(Balances::free_balance(EVE), 1_000);
+		assert!(RngDao::bounty_contributions(cycle_id).is_empty());
+	});
+}
+
+#[test]
+fn top_ups_of_completed_cycles_are_not_refunded() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		assert_ok!(RngDao::top_up_bounty(RuntimeOrigin::signed(EVE), cycle_id, 50));
+		let bob_secret = 807_u64;
+		let bob_hash = commit(cycle_id, BOB, bob_secret);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		System::set_block_number(RngDao::second_phase_start(&RngDao::cycles(cycle_id).unwrap()));
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(bob_secret),
+			SALT
+		));
+
+		System::set_block_number(RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap()));
+		assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::Completed);
+		assert_eq!(Balances::free_balance(EVE), 1_000 - 50);
+		assert!(RngDao::bounty_contributions(cycle_id).is_empty());
+	});
+}
+
+#[test]
+fn too_many_contributors_error() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		// MaxContributors is 2
+		assert_ok!(RngDao::top_up_bounty(RuntimeOrigin::signed(BOB), cycle_id, 10));
+		assert_ok!(RngDao::top_up_bounty(RuntimeOrigin::signed(EVE), cycle_id, 10));
+		assert_noop!(
+			RngDao::top_up_bounty(RuntimeOrigin::signed(CHARLIE), cycle_id, 10),
+			Error::<Test>::TooManyContributors
+		);
+		// those who contributed already and the creator can still top up
+		assert_ok!(RngDao::top_up_bounty(RuntimeOrigin::signed(BOB), cycle_id, 10));
+		assert_ok!(RngDao::top_up_bounty(RuntimeOrigin::signed(ALICE), cycle_id, 10));
+		assert_eq!(RngDao::bounty_contributions(cycle_id).into_inner(), vec![(BOB, 20), (EVE, 10)]);
+	});
+}
+
+#[test]
+fn cycle_fails_with_fewer_reveals_than_min_revealers() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		let cycle_id = 0_u128;
+		let params = CycleParameters { min_revealers: 2_u8, ..RngDao::default_cycle_parameters() };
+		assert_ok!(RngDao::create_new_rng_cycle(
+			RuntimeOrigin::signed(ALICE),
+			bounty,
+			Some(params),
+			None
+		));
+		assert_ok!(RngDao::send_hash(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			commit(cycle_id, BOB, 1)
+		));
+		assert_ok!(RngDao::send_hash(
+			RuntimeOrigin::signed(CHARLIE),
+			cycle_id,
+			commit(cycle_id, CHARLIE, 2)
+		));
+
+		let cycle = RngDao::cycles(cycle_id).unwrap();
+		System::set_block_number(RngDao::second_phase_start(&cycle));
+		assert_ok!(RngDao::reveal_secret(RuntimeOrigin::signed(BOB), cycle_id, to_secret(1), SALT));
+		// deposit is back, share waits for the second reveal
+		assert_eq!(Balances::free_balance(BOB), 1_000);
+		assert_eq!(RngDao::unpaid_shares(cycle_id, BOB), Some(bounty / 3));
+
+		let deadline = RngDao::cycle_deadline(&cycle);
+		System::set_block_number(deadline);
+		RngDao::on_initialize(deadline);
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::Failed);
+		assert_eq!(RngDao::unpaid_shares(cycle_id, BOB), None);
+		assert!(RngDao::revealed_entropies(cycle_id).is_empty());
+		// creator gets the whole bounty back, CHARLIE's deposit is slashed
+		assert_eq!(Balances::free_balance(ALICE), 1_000);
+		assert_eq!(Balances::free_balance(CHARLIE), 1_000 - 300);
+		assert_eq!(Balances::free_balance(TREASURY), 300);
+	});
+}
+
+#[test]
+fn shares_are_paid_once_min_revealers_revealed() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+		let bounty = 150;
+		let cycle_id = 0_u128;
+		let params = CycleParameters { min_revealers: 2_u8, ..RngDao::default_cycle_parameters() };
+		assert_ok!(RngDao::create_new_rng_cycle(
+			RuntimeOrigin::signed(ALICE),
+			bounty,
+			Some(params),
+			None
+		));
+		assert_ok!(RngDao::send_hash(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			commit(cycle_id, BOB, 1)
+		));
+		assert_ok!(RngDao::send_hash(
+			RuntimeOrigin::signed(CHARLIE),
+			cycle_id,
+			commit(cycle_id, CHARLIE, 2)
+		));
+
+		let cycle = RngDao::cycles(cycle_id).unwrap();
+		System::set_block_number(RngDao::second_phase_start(&cycle));
+		assert_ok!(RngDao::reveal_secret(RuntimeOrigin::signed(BOB), cycle_id, to_secret(1), SALT));
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(CHARLIE),
+			cycle_id,
+			to_secret(2),
+			SALT
+		));
+		// 2 generators + our profit share
+		assert_eq!(Balances::free_balance(BOB), 1_000 + bounty / 3);
+		assert_eq!(Balances::free_balance(CHARLIE), 1_000 + bounty / 3);
+		assert_eq!(RngDao::unpaid_shares(cycle_id, BOB), None);
+
+		let deadline = RngDao::cycle_deadline(&cycle);
+		System::set_block_number(deadline);
+		RngDao::on_initialize(deadline);
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::Completed);
+	});
+}
+
+#[test]
+fn commit_phase_is_extended_while_commitments_are_missing() {
+	ExtBuilder::default().with_commit_extension(4).build().execute_with(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		let params = CycleParameters { min_revealers: 2_u8, ..RngDao::default_cycle_parameters() };
+		assert_ok!(RngDao::create_new_rng_cycle(
+			RuntimeOrigin::signed(ALICE),
+			150,
+			Some(params),
+			None
+		));
+		assert_ok!(RngDao::send_hash(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			commit(cycle_id, BOB, 1)
+		));
+
+		// commit phase would have ended at block 1 + 3 + 2 with a single commitment
+		System::set_block_number(6);
+		assert_eq!(RngDao::cycle_details(cycle_id).unwrap().phase, CycleStatus::BotCommitPhase);
+		assert_ok!(RngDao::send_hash(
+			RuntimeOrigin::signed(CHARLIE),
+			cycle_id,
+			commit(cycle_id, CHARLIE, 2)
+		));
+		let cycle = RngDao::cycles(cycle_id).unwrap();
+		assert_eq!(cycle.commit_extensions, 1);
+		assert_eq!(RngDao::second_phase_start(&cycle), 6 + 4);
+
+		// finalisation follows the new deadline
+		for block in 7..RngDao::cycle_deadline(&cycle) {
+			System::set_block_number(block);
+			RngDao::on_initialize(block);
+		}
+		assert_ok!(RngDao::reveal_secret(RuntimeOrigin::signed(BOB), cycle_id, to_secret(1), SALT));
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(CHARLIE),
+			cycle_id,
+			to_secret(2),
+			SALT
+		));
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::RevealPhase);
+		let deadline = RngDao::cycle_deadline(&cycle);
+		System::set_block_number(deadline);
+		RngDao::on_initialize(deadline);
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::Completed);
+	});
+}
+
+#[test]
+fn commit_phase_extensions_are_limited() {
+	ExtBuilder::default().with_commit_extension(4).build().execute_with(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		let params = CycleParameters { min_revealers: 2_u8, ..RngDao::default_cycle_parameters() };
+		assert_ok!(RngDao::create_new_rng_cycle(
+			RuntimeOrigin::signed(ALICE),
+			150,
+			Some(params),
+			None
+		));
+		assert_ok!(RngDao::send_hash(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			commit(cycle_id, BOB, 1)
+		));
+
+		// 1 + 3 + 2 + 2 extensions of 4 blocks + 5
+		for block in 2..19 {
+			System::set_block_number(block);
+			RngDao::on_initialize(block);
+		}
+		let details = RngDao::cycle_details(cycle_id).unwrap();
+		assert_eq!(details.phase, CycleStatus::RevealPhase);
+		assert_eq!(details.cycle.commit_extensions, 2);
+		assert_eq!(details.deadline, 19);
+		assert_ok!(RngDao::reveal_secret(RuntimeOrigin::signed(BOB), cycle_id, to_secret(1), SALT));
+
+		System::set_block_number(19);
+		RngDao::on_initialize(19);
+		assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::Failed);
+		assert_eq!(Balances::free_balance(ALICE), 1_000);
+		assert_eq!(Balances::free_balance(BOB), 1_000);
+	});
+}
+
+#[test]
+fn withholding_last_secret_costs_scaled_deposit() {
+	ExtBuilder::default()
+		.with_deposit_ratio(Perbill::one())
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			let bounty = 450;
+			let cycle_id = 0_u128;
+			assert_ok!(RngDao::create_new_rng_cycle(
+				RuntimeOrigin::signed(ALICE),
+				bounty,
+				None,
+				None
+			));
+			let deposit = RngDao::cycles(cycle_id).unwrap().params.deposit;
+			assert_eq!(deposit, bounty);
+			assert_ok!(RngDao::send_hash(
+				RuntimeOrigin::signed(BOB),
+				cycle_id,
+				commit(cycle_id, BOB, 1)
+			));
+			assert_ok!(RngDao::send_hash(
+				RuntimeOrigin::signed(CHARLIE),
+				cycle_id,
+				commit(cycle_id, CHARLIE, 2)
+			));
+
+			let cycle = RngDao::cycles(cycle_id).unwrap();
+			System::set_block_number(RngDao::second_phase_start(&cycle));
+			assert_ok!(RngDao::reveal_secret(
+				RuntimeOrigin::signed(BOB),
+				cycle_id,
+				to_secret(1),
+				SALT
+			));
+			// CHARLIE knows both outcomes now and keeps its secret, as it prefers BOB's alone
+			let revealed = RngDao::revealed_entropies(cycle_id).to_vec();
+			assert_eq!(revealed, vec![entropy(1)]);
+			assert_ne!(entropy(1), entropy(1) ^ entropy(2));
+
+			System::set_block_number(RngDao::cycle_deadline(&cycle));
+			assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+			assert_eq!(RngDao::cycles(cycle_id).unwrap().random_number, entropy(1));
+			// the choice cost CHARLIE as much as the whole bounty, not only its share of it
+			assert_eq!(Balances::free_balance(CHARLIE), 1_000 - bounty);
+			assert_eq!(Balances::free_balance(BOB), 1_000 + bounty / 3);
+			assert_eq!(Balances::free_balance(TREASURY), deposit + bounty - bounty / 3);
+		});
+}
+
+#[test]
+fn deposits_follow_bounty_until_first_commitment() {
+	ExtBuilder::default()
+		.with_deposit_ratio(Perbill::from_percent(50))
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			let cycle_id = 0_u128;
+			let deposit = |cycle_id| RngDao::cycles(cycle_id).unwrap().params.deposit;
+			assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 200, None, None));
+			// deposit of the parameters is higher than half the bounty
+			assert_eq!(deposit(cycle_id), 300);
+			assert_ok!(RngDao::top_up_bounty(RuntimeOrigin::signed(EVE), cycle_id, 600));
+			assert_eq!(deposit(cycle_id), 400);
+
+			assert_ok!(RngDao::send_hash(
+				RuntimeOrigin::signed(BOB),
+				cycle_id,
+				commit(cycle_id, BOB, 1)
+			));
+			assert_eq!(Balances::free_balance(BOB), 1_000 - 400);
+			assert_ok!(RngDao::top_up_bounty(RuntimeOrigin::signed(EVE), cycle_id, 200));
+			assert_eq!(deposit(cycle_id), 400);
+
+			// deposits are paid in the native token, asset bounties do not scale them
+			assert_ok!(RngDao::set_min_asset_bounty(RuntimeOrigin::root(), ASSET, Some(100)));
+			assert_ok!(RngDao::create_new_rng_cycle(
+				RuntimeOrigin::signed(ALICE),
+				1_000,
+				None,
+				Some(ASSET)
+			));
+			assert_eq!(deposit(1_u128), 300);
+		});
+}
+
+#[test]
+fn scaled_deposit_is_at_most_the_maximum_deposit() {
+	ExtBuilder::default()
+		.with_deposit_ratio(Perbill::one())
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			let cycle_id = 0_u128;
+			let max_deposit = <Test as crate::Config>::MaxCycleParameters::get().deposit;
+			assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 600, None, None));
+			assert_eq!(RngDao::cycles(cycle_id).unwrap().params.deposit, max_deposit);
+			assert_ok!(RngDao::top_up_bounty(RuntimeOrigin::signed(EVE), cycle_id, 300));
+			assert_eq!(RngDao::cycles(cycle_id).unwrap().params.deposit, max_deposit);
+			// generators can still afford it
+			assert_ok!(RngDao::send_hash(
+				RuntimeOrigin::signed(BOB),
+				cycle_id,
+				commit(cycle_id, BOB, 1)
+			));
+			assert_eq!(Balances::free_balance(BOB), 1_000 - max_deposit);
+		});
+}
+
+#[test]
+fn genesis_config_starts_cycles_and_registers_bots() {
+	let params =
+		CycleParameters { second_phase_duration: 10_u32, ..RngDao::default_cycle_parameters() };
+	ExtBuilder::default()
+		.with_genesis_cycles(5, vec![(ALICE, 150, None), (BOB, 200, Some(params.clone()))])
+		.build()
+		.execute_with(|| {
+			assert_eq!(RngDao::get_cycle_count(), 7_u128);
+			assert!(RngDao::cycles(4_u128).is_none());
+			let cycle = RngDao::cycles(5_u128).unwrap();
+			assert_eq!((cycle.creator, cycle.bounty, cycle.started), (ALICE, 150, 0));
+			assert_eq!(cycle.params, RngDao::default_cycle_parameters());
+			assert_eq!(RngDao::cycles(6_u128).unwrap().params, params);
+			assert_eq!(Balances::free_balance(ALICE), 1_000 - 150);
+			assert_eq!(Balances::free_balance(BOB), 1_000 - 200);
+			assert!(RngDao::is_bot(&BOT));
+
+			// genesis cycles run like any other one
+			System::set_block_number(1);
+			assert_ok!(RngDao::send_hash(
+				RuntimeOrigin::signed(CHARLIE),
+				5_u128,
+				commit(5_u128, CHARLIE, 1)
+			));
+			let deadline = RngDao::cycle_deadline(&RngDao::cycles(5_u128).unwrap());
+			assert!(RngDao::cycle_deadlines(deadline).contains(&5_u128));
+		});
+}
+
+#[test]
+fn parameters_set_by_admin_override_constants() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		let constants = RngDao::default_cycle_parameters();
+		let parameters = Parameters {
+			min_bounty: 200_u128,
+			cycle_parameters: CycleParameters {
+				delay_before_bots: 5_u32,
+				deposit: 400_u128,
+				..constants.clone()
+			},
+		};
+		assert_noop!(
+			RngDao::set_parameters(RuntimeOrigin::signed(ALICE), Some(parameters.clone())),
+			BadOrigin
+		);
+		assert_ok!(RngDao::set_parameters(RuntimeOrigin::root(), Some(parameters.clone())));
+		System::assert_last_event(
+			Event::ParametersUpdated { parameters: Some(parameters.clone()) }.into(),
+		);
+		assert_eq!(RngDao::pallet_parameters(), Some(parameters.clone()));
+
+		assert_noop!(
+			RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None),
+			Error::<Test>::BountyMustBeGreaterThanMinBounty
+		);
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 200, None, None));
+		assert_eq!(RngDao::cycles(1_u128).unwrap().params, parameters.cycle_parameters);
+		// cycles already in flight keep their parameters
+		assert_eq!(RngDao::cycles(0_u128).unwrap().params, constants);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), 0_u128, commit(0_u128, BOB, 1)));
+		assert_eq!(Balances::free_balance(BOB), 1_000 - 300);
+
+		assert_ok!(RngDao::set_parameters(RuntimeOrigin::root(), None));
+		System::assert_last_event(Event::ParametersUpdated { parameters: None }.into());
+		assert_eq!(RngDao::default_cycle_parameters(), constants);
+	});
+}
+
+#[test]
+fn invalid_parameters_are_rejected() {
+	ExtBuilder::default().build().execute_with(|| {
+		let valid = Parameters {
+			min_bounty: 100_u128,
+			cycle_parameters: RngDao::default_cycle_parameters(),
+		};
+		let with_cycle_parameters =
+			|cycle_parameters| Parameters { cycle_parameters, ..valid.clone() };
+		let cycle_parameters = valid.cycle_parameters.clone();
+		for parameters in [
+			Parameters { min_bounty: 0_u128, ..valid.clone() },
+			with_cycle_parameters(CycleParameters { deposit: 0_u128, ..cycle_parameters.clone() }),
+			with_cycle_parameters(CycleParameters {
+				delay_before_bots: 0_u32,
+				..cycle_parameters.clone()
+			}),
+			with_cycle_parameters(CycleParameters {
+				delay_before_second_phase: 0_u32,
+				..cycle_parameters.clone()
+			}),
+			with_cycle_parameters(CycleParameters {
+				second_phase_duration: 0_u32,
+				..cycle_parameters.clone()
+			}),
+			with_cycle_parameters(CycleParameters {
+				min_revealers: 4_u8,
+				..cycle_parameters.clone()
+			}),
+			// above MaxCycleParameters
+			with_cycle_parameters(CycleParameters {
+				deposit: 501_u128,
+				..cycle_parameters.clone()
+			}),
+		] {
+			assert_noop!(
+				RngDao::set_parameters(RuntimeOrigin::root(), Some(parameters)),
+				Error::<Test>::InvalidParameters
+			);
+		}
+		assert_ok!(RngDao::set_parameters(RuntimeOrigin::root(), Some(valid)));
+	});
+}
+
+#[test]
+fn try_state_holds_while_cycles_run() {
+	for builder in [ExtBuilder::default(), ExtBuilder::default().with_reserves()] {
+		builder.build_and_execute(|| {
+			System::set_block_number(1);
+			let cycle_id = 0_u128;
+			let params =
+				CycleParameters { min_revealers: 2_u8, ..RngDao::default_cycle_parameters() };
+			assert_ok!(RngDao::create_new_rng_cycle(
+				RuntimeOrigin::signed(ALICE),
+				150,
+				Some(params),
+				None
+			));
+			assert_ok!(RngDao::do_try_state());
+			// creator takes part too
+			for (who, secret) in [(ALICE, 9897_u64), (BOB, 807), (CHARLIE, 120019)] {
+				assert_ok!(RngDao::send_hash(
+					RuntimeOrigin::signed(who),
+					cycle_id,
+					commit(cycle_id, who, secret)
+				));
+				assert_ok!(RngDao::do_try_state());
+			}
+
+			System::set_block_number(RngDao::second_phase_start(
+				&RngDao::cycles(cycle_id).unwrap(),
+			));
+			// share of BOB is unpaid until CHARLIE reveals
+			for (who, secret) in [(BOB, 807_u64), (CHARLIE, 120019)] {
+				assert_ok!(RngDao::reveal_secret(
+					RuntimeOrigin::signed(who),
+					cycle_id,
+					to_secret(secret),
+					SALT
+				));
+				assert_ok!(RngDao::do_try_state());
+			}
+
+			let deadline = RngDao::cycle_deadline(&RngDao::cycles(cycle_id).unwrap());
+			System::set_block_number(deadline);
+			RngDao::on_initialize(deadline);
+			assert_eq!(RngDao::cycles(cycle_id).unwrap().status, CycleStatus::Completed);
+		});
+	}
+}
+
+#[test]
+fn try_state_detects_broken_invariants() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		let bob_hash = commit(cycle_id, BOB, 807);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		assert_ok!(RngDao::do_try_state());
+
+		crate::CycleCount::<Test>::put(cycle_id);
+		assert_eq!(RngDao::do_try_state(), Err("cycle id is not below cycle count"));
+		crate::CycleCount::<Test>::put(cycle_id + 1);
+
+		let cycle = RngDao::cycles(cycle_id).unwrap();
+		crate::Cycles::<Test>::insert(cycle_id, RngCycle { revealed_count: 2, ..cycle.clone() });
+		assert_eq!(RngDao::do_try_state(), Err("more reveals than generators"));
+		crate::Cycles::<Test>::insert(cycle_id, cycle);
+
+		let generator = RngDao::generators(cycle_id, BOB).unwrap();
+		crate::Generators::<Test>::remove(cycle_id, BOB);
+		assert_eq!(
+			RngDao::do_try_state(),
+			Err("generators count does not match generators and reveals")
+		);
+		crate::Generators::<Test>::insert(cycle_id, BOB, generator);
+
+		crate::Reveals::<Test>::insert(cycle_id, CHARLIE, to_secret(1));
+		assert_eq!(RngDao::do_try_state(), Err("more reveals stored than revealed"));
+		crate::Reveals::<Test>::remove(cycle_id, CHARLIE);
+
+		assert_ok!(Balances::transfer(
+			RuntimeOrigin::signed(RngDao::account_id(&cycle_id)),
+			TOM,
+			1
+		));
+		assert_eq!(RngDao::do_try_state(), Err("cycle does not hold its bounty and deposits"));
+	});
+}
+
+#[test]
+fn randomness_history_keeps_latest_completed_cycles() {
+	use crate::RandomnessRecord;
+
+	ExtBuilder::default().build_and_execute(|| {
+		for (cycle_id, secret) in [(0_u128, 9897_u64), (1, 807), (2, 120019)] {
+			System::set_block_number(1 + 10 * cycle_id as u32);
+			assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+			let hash = commit(cycle_id, BOB, secret);
+			assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, hash));
+			let cycle = RngDao::cycles(cycle_id).unwrap();
+			System::set_block_number(RngDao::second_phase_start(&cycle));
+			assert_ok!(RngDao::reveal_secret(
+				RuntimeOrigin::signed(BOB),
+				cycle_id,
+				to_secret(secret),
+				SALT
+			));
+			System::set_block_number(RngDao::cycle_deadline(&cycle));
+			assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+		}
+
+		// MaxHistory is 2, the first cycle completed at block 11 was dropped
+		let record = |cycle_id, block, secret| RandomnessRecord {
+			cycle_id,
+			block,
+			random_number: entropy(secret),
+		};
+		assert_eq!(
+			RngDao::randomness_history().to_vec(),
+			vec![record(1_u128, 21_u32, 807), record(2, 31, 120019)]
+		);
+		assert_eq!(RngDao::randomness_at(11), None);
+		assert_eq!(RngDao::randomness_at(21), Some(record(1, 21, 807)));
+		assert_eq!(RngDao::randomness_at(30), Some(record(1, 21, 807)));
+		assert_eq!(RngDao::randomness_at(100), Some(record(2, 31, 120019)));
+	});
+}
+
+#[test]
+fn reap_cycle_removes_finalised_cycles_after_retention_period() {
+	use frame_support::dispatch::Pays;
+
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		let bob_hash = commit(cycle_id, BOB, 807);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, bob_hash));
+		let charlie_hash = commit(cycle_id, CHARLIE, 120019);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(CHARLIE), cycle_id, charlie_hash));
+		let cycle = RngDao::cycles(cycle_id).unwrap();
+		System::set_block_number(RngDao::second_phase_start(&cycle));
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(807),
+			SALT
+		));
+
+		let deadline = RngDao::cycle_deadline(&cycle);
+		System::set_block_number(deadline);
+		assert_noop!(
+			RngDao::reap_cycle(RuntimeOrigin::signed(TOM), cycle_id),
+			Error::<Test>::CycleNotFinalised
+		);
+		assert_ok!(RngDao::get_random_number(RuntimeOrigin::signed(ALICE), cycle_id));
+		System::set_block_number(deadline + 10 /*RetentionPeriod*/ - 1);
+		assert_noop!(
+			RngDao::reap_cycle(RuntimeOrigin::signed(TOM), cycle_id),
+			Error::<Test>::RetentionPeriodNotOver
+		);
+
+		System::set_block_number(deadline + 10 /*RetentionPeriod*/);
+		let reaped = RngDao::reap_cycle(RuntimeOrigin::signed(TOM), cycle_id).unwrap();
+		assert_eq!(reaped.pays_fee, Pays::No);
+		System::assert_last_event(Event::CycleReaped { cycle_id }.into());
+		assert_eq!(RngDao::cycles(cycle_id), None);
+		// CHARLIE did not reveal
+		assert_eq!(RngDao::generators(cycle_id, CHARLIE), None);
+		assert_eq!(RngDao::reveals(cycle_id, BOB), None);
+		assert_eq!(RngDao::randomness_at(deadline).unwrap().random_number, entropy(807));
+		assert_noop!(
+			RngDao::reap_cycle(RuntimeOrigin::signed(TOM), cycle_id),
+			Error::<Test>::NoCycleFound
+		);
+	});
+}
+
+#[test]
+fn consumers_get_random_numbers_they_requested() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		assert_noop!(
+			RngDao::request_randomness(*b"unknown_", cycle_id),
+			Error::<Test>::UnknownCallback
+		);
+		assert_noop!(RngDao::request_randomness(LOTTERY, 1), Error::<Test>::NoCycleFound);
+		assert_eq!(RngDao::request_randomness(LOTTERY, cycle_id), Ok(0));
+		System::assert_last_event(
+			Event::RandomnessRequested { request_id: 0, cycle_id, callback: LOTTERY }.into(),
+		);
+		assert_eq!(RngDao::request_randomness(LOTTERY, cycle_id), Ok(1));
+		let hash = commit(cycle_id, BOB, 807);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, hash));
+
+		let cycle = RngDao::cycles(cycle_id).unwrap();
+		System::set_block_number(RngDao::second_phase_start(&cycle));
+		// secrets are being revealed, the random number is no secret anymore
+		assert_noop!(RngDao::request_randomness(LOTTERY, cycle_id), Error::<Test>::CommitPhaseOver);
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(807),
+			SALT
+		));
+
+		let deadline = RngDao::cycle_deadline(&cycle);
+		System::set_block_number(deadline);
+		RngDao::on_initialize(deadline);
+		assert_eq!(LotteryDraws::get(), vec![(0, entropy(807)), (1, entropy(807))]);
+		System::assert_last_event(Event::RandomnessDelivered { request_id: 1 }.into());
+		assert_eq!(RngDao::randomness_requests(cycle_id).len(), 0);
+		assert!(RngDao::queued_callbacks().is_empty());
+		assert_eq!(RngDao::pending_requests(), 0);
+	});
+}
+
+#[test]
+fn callbacks_over_max_callback_weight_wait_for_next_blocks() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		for request_id in 0..3 {
+			assert_eq!(RngDao::request_randomness(LOTTERY, cycle_id), Ok(request_id));
+		}
+		let hash = commit(cycle_id, BOB, 807);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, hash));
+		let cycle = RngDao::cycles(cycle_id).unwrap();
+		System::set_block_number(RngDao::second_phase_start(&cycle));
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(807),
+			SALT
+		));
+
+		let deadline = RngDao::cycle_deadline(&cycle);
+		System::set_block_number(deadline);
+		RngDao::on_initialize(deadline);
+		// MaxCallbackWeight of 100 fits two draws of 40
+		assert_eq!(LotteryDraws::get(), vec![(0, entropy(807)), (1, entropy(807))]);
+		assert_eq!(RngDao::queued_callbacks().len(), 1);
+		assert_eq!(RngDao::pending_requests(), 1);
+
+		System::set_block_number(deadline + 1);
+		RngDao::on_initialize(deadline + 1);
+		assert_eq!(LotteryDraws::get()[2], (2, entropy(807)));
+		assert!(RngDao::queued_callbacks().is_empty());
+		assert_eq!(RngDao::pending_requests(), 0);
+	});
+}
+
+#[test]
+fn requests_are_limited_and_dropped_with_their_cycle() {
+	use frame_support::weights::Weight;
+
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(BOB), 150, None, None));
+		LotteryWeight::set(Weight::from_ref_time(101));
+		assert_noop!(RngDao::request_randomness(LOTTERY, 0), Error::<Test>::CallbackTooHeavy);
+		LotteryWeight::set(Weight::from_ref_time(40));
+		for _ in 0..3 {
+			assert_ok!(RngDao::request_randomness(LOTTERY, 0));
+		}
+		// MaxRequestsPerConsumer is 3
+		assert_noop!(
+			RngDao::request_randomness(LOTTERY, 1),
+			Error::<Test>::TooManyConsumerRequests
+		);
+		assert_ok!(RngDao::request_randomness(RAFFLE, 1));
+		// MaxPendingRequests is 4
+		assert_noop!(RngDao::request_randomness(RAFFLE, 1), Error::<Test>::TooManyRequests);
+
+		assert_ok!(RngDao::cancel_cycle(RuntimeOrigin::signed(ALICE), 0));
+		System::assert_has_event(Event::RandomnessRequestsDropped { cycle_id: 0 }.into());
+		assert_eq!(RngDao::pending_requests(), 1);
+		assert_eq!(RngDao::consumer_requests(LOTTERY), 0);
+
+		// nobody committed to the cycle of BOB
+		let deadline = RngDao::cycle_deadline(&RngDao::cycles(1).unwrap());
+		System::set_block_number(deadline);
+		RngDao::on_initialize(deadline);
+		assert_eq!(RngDao::cycles(1).unwrap().status, CycleStatus::Failed);
+		System::assert_has_event(Event::RandomnessRequestsDropped { cycle_id: 1 }.into());
+		assert_eq!(RngDao::pending_requests(), 0);
+		assert_eq!(RngDao::consumer_requests(RAFFLE), 0);
+		assert!(LotteryDraws::get().is_empty());
+		assert!(RaffleDraws::get().is_empty());
+	});
+}
+
+#[test]
+fn requests_which_never_fit_are_dropped() {
+	use frame_support::weights::Weight;
+
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		assert_eq!(RngDao::request_randomness(LOTTERY, cycle_id), Ok(0));
+		assert_eq!(RngDao::request_randomness(LOTTERY, cycle_id), Ok(1));
+		assert_eq!(RngDao::request_randomness(RAFFLE, cycle_id), Ok(2));
+		let hash = commit(cycle_id, BOB, 807);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, hash));
+		let cycle = RngDao::cycles(cycle_id).unwrap();
+		System::set_block_number(RngDao::second_phase_start(&cycle));
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(807),
+			SALT
+		));
+		// an upgrade of the lottery makes it too heavy for MaxCallbackWeight of 100
+		LotteryWeight::set(Weight::from_ref_time(101));
+
+		let deadline = RngDao::cycle_deadline(&cycle);
+		System::set_block_number(deadline);
+		RngDao::on_initialize(deadline);
+		System::assert_has_event(Event::RandomnessRequestDropped { request_id: 0 }.into());
+		System::assert_has_event(Event::RandomnessRequestDropped { request_id: 1 }.into());
+		System::assert_last_event(Event::RandomnessDelivered { request_id: 2 }.into());
+		assert!(LotteryDraws::get().is_empty());
+		assert_eq!(RaffleDraws::get(), vec![(2, entropy(807))]);
+		assert!(RngDao::queued_callbacks().is_empty());
+		assert_eq!(RngDao::pending_requests(), 0);
+		assert_eq!(RngDao::consumer_requests(LOTTERY), 0);
+		LotteryWeight::set(Weight::from_ref_time(40));
+	});
+}
+
+#[test]
+fn consumer_using_up_its_requests_does_not_block_others() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+		let cycle_id = 0_u128;
+		assert_ok!(RngDao::create_new_rng_cycle(RuntimeOrigin::signed(ALICE), 150, None, None));
+		for request_id in 0..3 {
+			assert_eq!(RngDao::request_randomness(LOTTERY, cycle_id), Ok(request_id));
+		}
+		assert_noop!(
+			RngDao::request_randomness(LOTTERY, cycle_id),
+			Error::<Test>::TooManyConsumerRequests
+		);
+		assert_eq!(RngDao::request_randomness(RAFFLE, cycle_id), Ok(3));
+		let hash = commit(cycle_id, BOB, 807);
+		assert_ok!(RngDao::send_hash(RuntimeOrigin::signed(BOB), cycle_id, hash));
+
+		let cycle = RngDao::cycles(cycle_id).unwrap();
+		System::set_block_number(RngDao::second_phase_start(&cycle));
+		assert_ok!(RngDao::reveal_secret(
+			RuntimeOrigin::signed(BOB),
+			cycle_id,
+			to_secret(807),
+			SALT
+		));
+		let deadline = RngDao::cycle_deadline(&cycle);
+		System::set_block_number(deadline);
+		RngDao::on_initialize(deadline);
+		// two lottery draws fit in MaxCallbackWeight, the raffle waits behind the third one
+		assert_eq!(LotteryDraws::get(), vec![(0, entropy(807)), (1, entropy(807))]);
+		assert!(RaffleDraws::get().is_empty());
+		assert_eq!(RngDao::consumer_requests(LOTTERY), 1);
+
+		System::set_block_number(deadline + 1);
+		RngDao::on_initialize(deadline + 1);
+		assert_eq!(LotteryDraws::get().len(), 3);
+		assert_eq!(RaffleDraws::get(), vec![(3, entropy(807))]);
+		assert_eq!(RngDao::consumer_requests(LOTTERY), 0);
+		assert_eq!(RngDao::consumer_requests(RAFFLE), 0);
+	});
+}
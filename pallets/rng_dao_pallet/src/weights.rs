@@ -1,42 +1,415 @@
-use frame_support::weights::Weight;
+//! Weights for `pallet_rng_dao`.
+//!
+//! These are hand-written estimates, not benchmark results, as the benchmarks in `benchmarking.rs`
+//! have not been run on reference hardware yet. Reads and writes are counted from the storage a
+//! call accesses in the worst case, listed above each function, proof sizes follow from the maximum
+//! encoded length of those items and execution times are rounded up guesses. `u` is the number of
+//! generators of a cycle who did not reveal and `r` the number of those who did, `c` the number of
+//! accounts which topped up a bounty and `n` the number of queued requests for randomness handled
+//! in a block. `scripts/benchmark.sh` replaces this file with the weights generated from the
+//! benchmarks, it has to be run on the reference hardware of the chain.
+
+#![allow(unused_parens)]
+
+use frame_support::{
+	traits::Get,
+	weights::{constants::RocksDbWeight, Weight},
+};
 use sp_std::marker::PhantomData;
 
-pub trait RngDaoWeightInfo {
+/// Weight functions needed for pallet_rng_dao.
+pub trait WeightInfo {
 	fn create_new_rng_cycle() -> Weight;
+	fn create_new_rng_cycle_with_asset() -> Weight;
 	fn send_hash() -> Weight;
-	fn reveal_secret() -> Weight;
-	fn get_random_number() -> Weight;
+	fn reveal_secret(r: u32) -> Weight;
+	fn get_random_number(u: u32, r: u32) -> Weight;
+	fn finalise_cycle(u: u32, r: u32) -> Weight;
+	fn register_bot() -> Weight;
+	fn deregister_bot() -> Weight;
+	fn set_bot_share_policy() -> Weight;
+	fn set_min_asset_bounty() -> Weight;
+	fn cancel_cycle() -> Weight;
+	fn refund_bounty(c: u32) -> Weight;
+	fn top_up_bounty() -> Weight;
+	fn set_parameters() -> Weight;
+	fn reap_cycle(u: u32, r: u32) -> Weight;
+	fn deliver_randomness(n: u32) -> Weight;
 }
 
-pub struct RuntimeWeight<T>(PhantomData<T>);
-
-impl<T: frame_system::Config> RngDaoWeightInfo for RuntimeWeight<T> {
+/// Estimated weights for pallet_rng_dao, see the module documentation.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: RngDao PalletParameters (r:1 w:0)
+	// Storage: RngDao CycleCount (r:1 w:1)
+	// Storage: RngDao CycleDeadlines (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: RngDao Cycles (r:0 w:1)
 	fn create_new_rng_cycle() -> Weight {
-		Weight::from_ref_time(10_000)
+		Weight::from_ref_time(50_000_000)
+			.saturating_add(Weight::from_proof_size(9_010))
+			.saturating_add(T::DbWeight::get().reads(5))
+			.saturating_add(T::DbWeight::get().writes(5))
+	}
+	// Storage: RngDao MinAssetBounties (r:1 w:0)
+	// Storage: RngDao CycleCount (r:1 w:1)
+	// Storage: RngDao CycleDeadlines (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Assets Asset (r:1 w:1)
+	// Storage: Assets Account (r:2 w:2)
+	// Storage: RngDao Cycles (r:0 w:1)
+	// Storage: RngDao BountyAssets (r:0 w:1)
+	fn create_new_rng_cycle_with_asset() -> Weight {
+		Weight::from_ref_time(65_000_000)
+			.saturating_add(Weight::from_proof_size(13_498))
+			.saturating_add(T::DbWeight::get().reads(7))
+			.saturating_add(T::DbWeight::get().writes(8))
 	}
+	// Storage: RngDao Bots (r:1 w:0)
+	// Storage: RngDao Cycles (r:1 w:1)
+	// Storage: RngDao Generators (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
 	fn send_hash() -> Weight {
-		Weight::from_ref_time(10_000)
+		Weight::from_ref_time(55_000_000)
+			.saturating_add(Weight::from_proof_size(12_945))
+			.saturating_add(T::DbWeight::get().reads(5))
+			.saturating_add(T::DbWeight::get().writes(4))
+	}
+	// Storage: RngDao Cycles (r:1 w:1)
+	// Storage: RngDao Generators (r:1 w:1)
+	// Storage: RngDao RevealedEntropies (r:1 w:1)
+	// Storage: RngDao BotSharePolicy (r:1 w:0)
+	// Storage: RngDao BountyAssets (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: RngDao Reveals (r:0 w:1)
+	// Storage: RngDao UnpaidShares (r:100 w:99)
+	fn reveal_secret(r: u32) -> Weight {
+		Weight::from_ref_time(75_000_000)
+			.saturating_add(Weight::from_ref_time(22_000_000).saturating_mul(r.into()))
+			.saturating_add(Weight::from_proof_size(26_689))
+			.saturating_add(Weight::from_proof_size(5_158).saturating_mul(r.into()))
+			.saturating_add(T::DbWeight::get().reads(8))
+			.saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(r.into())))
+			.saturating_add(T::DbWeight::get().writes(6))
+			.saturating_add(T::DbWeight::get().writes((2_u64).saturating_mul(r.into())))
+	}
+	// Storage: RngDao Cycles (r:1 w:1)
+	// Storage: RngDao BountyAssets (r:1 w:0)
+	// Storage: System Account (r:3 w:3)
+	// Storage: RngDao Generators (r:51 w:0)
+	// Storage: RngDao RevealedEntropies (r:1 w:1)
+	// Storage: RngDao LatestRandomNumber (r:0 w:1)
+	// Storage: RngDao RandomnessHistory (r:1 w:1)
+	// Storage: RngDao RandomnessRequests (r:1 w:1)
+	// Storage: RngDao QueuedCallbacks (r:1 w:1)
+	fn get_random_number(u: u32, r: u32) -> Weight {
+		Weight::from_ref_time(45_000_000)
+			.saturating_add(Weight::from_ref_time(20_000_000).saturating_mul(u.into()))
+			.saturating_add(Weight::from_ref_time(1_000_000).saturating_mul(r.into()))
+			.saturating_add(Weight::from_proof_size(45_159))
+			.saturating_add(Weight::from_proof_size(2_589).saturating_mul(u.into()))
+			.saturating_add(T::DbWeight::get().reads(9))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(u.into())))
+			.saturating_add(T::DbWeight::get().writes(9))
+	}
+	// Storage: RngDao Cycles (r:1 w:1)
+	// Storage: RngDao BountyAssets (r:1 w:0)
+	// Storage: System Account (r:3 w:3)
+	// Storage: RngDao Generators (r:51 w:0)
+	// Storage: RngDao RevealedEntropies (r:1 w:1)
+	// Storage: RngDao LatestRandomNumber (r:0 w:1)
+	// Storage: RngDao RandomnessHistory (r:1 w:1)
+	// Storage: RngDao RandomnessRequests (r:1 w:1)
+	// Storage: RngDao QueuedCallbacks (r:1 w:1)
+	fn finalise_cycle(u: u32, r: u32) -> Weight {
+		Weight::from_ref_time(45_000_000)
+			.saturating_add(Weight::from_ref_time(20_000_000).saturating_mul(u.into()))
+			.saturating_add(Weight::from_ref_time(1_000_000).saturating_mul(r.into()))
+			.saturating_add(Weight::from_proof_size(45_159))
+			.saturating_add(Weight::from_proof_size(2_589).saturating_mul(u.into()))
+			.saturating_add(T::DbWeight::get().reads(9))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(u.into())))
+			.saturating_add(T::DbWeight::get().writes(9))
+	}
+	// Storage: RngDao Bots (r:1 w:1)
+	fn register_bot() -> Weight {
+		Weight::from_ref_time(20_000_000)
+			.saturating_add(Weight::from_proof_size(2_523))
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: RngDao Bots (r:1 w:1)
+	fn deregister_bot() -> Weight {
+		Weight::from_ref_time(20_000_000)
+			.saturating_add(Weight::from_proof_size(2_523))
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: RngDao BotSharePolicy (r:0 w:1)
+	fn set_bot_share_policy() -> Weight {
+		Weight::from_ref_time(15_000_000).saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: RngDao MinAssetBounties (r:0 w:1)
+	fn set_min_asset_bounty() -> Weight {
+		Weight::from_ref_time(15_000_000).saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: RngDao Cycles (r:1 w:1)
+	// Storage: RngDao BountyAssets (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: RngDao Generators (r:1 w:0)
+	// Storage: RngDao CycleDeadlines (r:1 w:1)
+	// Storage: RngDao RandomnessRequests (r:1 w:1)
+	fn cancel_cycle() -> Weight {
+		Weight::from_ref_time(55_000_000)
+			.saturating_add(Weight::from_proof_size(19_218))
+			.saturating_add(T::DbWeight::get().reads(7))
+			.saturating_add(T::DbWeight::get().writes(5))
+	}
+	// Storage: RngDao BountyContributions (r:1 w:0)
+	// Storage: RngDao BountyAssets (r:17 w:0)
+	// Storage: System Account (r:17 w:17)
+	fn refund_bounty(c: u32) -> Weight {
+		Weight::from_ref_time(25_000_000)
+			.saturating_add(Weight::from_ref_time(20_000_000).saturating_mul(c.into()))
+			.saturating_add(Weight::from_proof_size(8_256))
+			.saturating_add(Weight::from_proof_size(5_152).saturating_mul(c.into()))
+			.saturating_add(T::DbWeight::get().reads(3))
+			.saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(c.into())))
+			.saturating_add(T::DbWeight::get().writes(1))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(c.into())))
+	}
+	// Storage: RngDao Cycles (r:1 w:1)
+	// Storage: RngDao BountyAssets (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: RngDao BountyContributions (r:1 w:1)
+	fn top_up_bounty() -> Weight {
+		Weight::from_ref_time(50_000_000)
+			.saturating_add(Weight::from_proof_size(11_164))
+			.saturating_add(T::DbWeight::get().reads(5))
+			.saturating_add(T::DbWeight::get().writes(4))
 	}
-	fn reveal_secret() -> Weight {
-		Weight::from_ref_time(10_000)
+	// Storage: RngDao PalletParameters (r:0 w:1)
+	fn set_parameters() -> Weight {
+		Weight::from_ref_time(20_000_000).saturating_add(T::DbWeight::get().writes(1))
 	}
-	fn get_random_number() -> Weight {
-		Weight::from_ref_time(10_000)
+	// Storage: RngDao Cycles (r:1 w:1)
+	// Storage: RngDao Generators (r:50 w:50)
+	// Storage: RngDao Reveals (r:50 w:50)
+	// Storage: RngDao BountyAssets (r:0 w:1)
+	// Storage: RngDao BountyContributions (r:0 w:1)
+	fn reap_cycle(u: u32, r: u32) -> Weight {
+		Weight::from_ref_time(30_000_000)
+			.saturating_add(Weight::from_ref_time(1_500_000).saturating_mul(u.into()))
+			.saturating_add(Weight::from_ref_time(1_500_000).saturating_mul(r.into()))
+			.saturating_add(Weight::from_proof_size(2_629))
+			.saturating_add(Weight::from_proof_size(2_589).saturating_mul(u.into()))
+			.saturating_add(Weight::from_proof_size(2_572).saturating_mul(r.into()))
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(u.into())))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(r.into())))
+			.saturating_add(T::DbWeight::get().writes(3))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(u.into())))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(r.into())))
+	}
+	// Storage: RngDao QueuedCallbacks (r:1 w:1)
+	// Storage: RngDao PendingRequests (r:1 w:1)
+	// Storage: RngDao ConsumerRequests (r:64 w:64)
+	fn deliver_randomness(n: u32) -> Weight {
+		Weight::from_ref_time(10_000_000)
+			.saturating_add(Weight::from_ref_time(5_000_000).saturating_mul(n.into()))
+			.saturating_add(Weight::from_proof_size(4_068))
+			.saturating_add(Weight::from_proof_size(2_503).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(2))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(n.into())))
 	}
 }
 
-// Used in mock runtime only
-impl RngDaoWeightInfo for () {
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	// Storage: RngDao PalletParameters (r:1 w:0)
+	// Storage: RngDao CycleCount (r:1 w:1)
+	// Storage: RngDao CycleDeadlines (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: RngDao Cycles (r:0 w:1)
 	fn create_new_rng_cycle() -> Weight {
-		Weight::from_ref_time(10_000)
+		Weight::from_ref_time(50_000_000)
+			.saturating_add(Weight::from_proof_size(9_010))
+			.saturating_add(RocksDbWeight::get().reads(5))
+			.saturating_add(RocksDbWeight::get().writes(5))
+	}
+	// Storage: RngDao MinAssetBounties (r:1 w:0)
+	// Storage: RngDao CycleCount (r:1 w:1)
+	// Storage: RngDao CycleDeadlines (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Assets Asset (r:1 w:1)
+	// Storage: Assets Account (r:2 w:2)
+	// Storage: RngDao Cycles (r:0 w:1)
+	// Storage: RngDao BountyAssets (r:0 w:1)
+	fn create_new_rng_cycle_with_asset() -> Weight {
+		Weight::from_ref_time(65_000_000)
+			.saturating_add(Weight::from_proof_size(13_498))
+			.saturating_add(RocksDbWeight::get().reads(7))
+			.saturating_add(RocksDbWeight::get().writes(8))
 	}
+	// Storage: RngDao Bots (r:1 w:0)
+	// Storage: RngDao Cycles (r:1 w:1)
+	// Storage: RngDao Generators (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
 	fn send_hash() -> Weight {
-		Weight::from_ref_time(10_000)
+		Weight::from_ref_time(55_000_000)
+			.saturating_add(Weight::from_proof_size(12_945))
+			.saturating_add(RocksDbWeight::get().reads(5))
+			.saturating_add(RocksDbWeight::get().writes(4))
+	}
+	// Storage: RngDao Cycles (r:1 w:1)
+	// Storage: RngDao Generators (r:1 w:1)
+	// Storage: RngDao RevealedEntropies (r:1 w:1)
+	// Storage: RngDao BotSharePolicy (r:1 w:0)
+	// Storage: RngDao BountyAssets (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: RngDao Reveals (r:0 w:1)
+	// Storage: RngDao UnpaidShares (r:100 w:99)
+	fn reveal_secret(r: u32) -> Weight {
+		Weight::from_ref_time(75_000_000)
+			.saturating_add(Weight::from_ref_time(22_000_000).saturating_mul(r.into()))
+			.saturating_add(Weight::from_proof_size(26_689))
+			.saturating_add(Weight::from_proof_size(5_158).saturating_mul(r.into()))
+			.saturating_add(RocksDbWeight::get().reads(8))
+			.saturating_add(RocksDbWeight::get().reads((2_u64).saturating_mul(r.into())))
+			.saturating_add(RocksDbWeight::get().writes(6))
+			.saturating_add(RocksDbWeight::get().writes((2_u64).saturating_mul(r.into())))
+	}
+	// Storage: RngDao Cycles (r:1 w:1)
+	// Storage: RngDao BountyAssets (r:1 w:0)
+	// Storage: System Account (r:3 w:3)
+	// Storage: RngDao Generators (r:51 w:0)
+	// Storage: RngDao RevealedEntropies (r:1 w:1)
+	// Storage: RngDao LatestRandomNumber (r:0 w:1)
+	// Storage: RngDao RandomnessHistory (r:1 w:1)
+	// Storage: RngDao RandomnessRequests (r:1 w:1)
+	// Storage: RngDao QueuedCallbacks (r:1 w:1)
+	fn get_random_number(u: u32, r: u32) -> Weight {
+		Weight::from_ref_time(45_000_000)
+			.saturating_add(Weight::from_ref_time(20_000_000).saturating_mul(u.into()))
+			.saturating_add(Weight::from_ref_time(1_000_000).saturating_mul(r.into()))
+			.saturating_add(Weight::from_proof_size(45_159))
+			.saturating_add(Weight::from_proof_size(2_589).saturating_mul(u.into()))
+			.saturating_add(RocksDbWeight::get().reads(9))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(u.into())))
+			.saturating_add(RocksDbWeight::get().writes(9))
+	}
+	// Storage: RngDao Cycles (r:1 w:1)
+	// Storage: RngDao BountyAssets (r:1 w:0)
+	// Storage: System Account (r:3 w:3)
+	// Storage: RngDao Generators (r:51 w:0)
+	// Storage: RngDao RevealedEntropies (r:1 w:1)
+	// Storage: RngDao LatestRandomNumber (r:0 w:1)
+	// Storage: RngDao RandomnessHistory (r:1 w:1)
+	// Storage: RngDao RandomnessRequests (r:1 w:1)
+	// Storage: RngDao QueuedCallbacks (r:1 w:1)
+	fn finalise_cycle(u: u32, r: u32) -> Weight {
+		Weight::from_ref_time(45_000_000)
+			.saturating_add(Weight::from_ref_time(20_000_000).saturating_mul(u.into()))
+			.saturating_add(Weight::from_ref_time(1_000_000).saturating_mul(r.into()))
+			.saturating_add(Weight::from_proof_size(45_159))
+			.saturating_add(Weight::from_proof_size(2_589).saturating_mul(u.into()))
+			.saturating_add(RocksDbWeight::get().reads(9))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(u.into())))
+			.saturating_add(RocksDbWeight::get().writes(9))
+	}
+	// Storage: RngDao Bots (r:1 w:1)
+	fn register_bot() -> Weight {
+		Weight::from_ref_time(20_000_000)
+			.saturating_add(Weight::from_proof_size(2_523))
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	// Storage: RngDao Bots (r:1 w:1)
+	fn deregister_bot() -> Weight {
+		Weight::from_ref_time(20_000_000)
+			.saturating_add(Weight::from_proof_size(2_523))
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	// Storage: RngDao BotSharePolicy (r:0 w:1)
+	fn set_bot_share_policy() -> Weight {
+		Weight::from_ref_time(15_000_000).saturating_add(RocksDbWeight::get().writes(1))
+	}
+	// Storage: RngDao MinAssetBounties (r:0 w:1)
+	fn set_min_asset_bounty() -> Weight {
+		Weight::from_ref_time(15_000_000).saturating_add(RocksDbWeight::get().writes(1))
+	}
+	// Storage: RngDao Cycles (r:1 w:1)
+	// Storage: RngDao BountyAssets (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: RngDao Generators (r:1 w:0)
+	// Storage: RngDao CycleDeadlines (r:1 w:1)
+	// Storage: RngDao RandomnessRequests (r:1 w:1)
+	fn cancel_cycle() -> Weight {
+		Weight::from_ref_time(55_000_000)
+			.saturating_add(Weight::from_proof_size(19_218))
+			.saturating_add(RocksDbWeight::get().reads(7))
+			.saturating_add(RocksDbWeight::get().writes(5))
+	}
+	// Storage: RngDao BountyContributions (r:1 w:0)
+	// Storage: RngDao BountyAssets (r:17 w:0)
+	// Storage: System Account (r:17 w:17)
+	fn refund_bounty(c: u32) -> Weight {
+		Weight::from_ref_time(25_000_000)
+			.saturating_add(Weight::from_ref_time(20_000_000).saturating_mul(c.into()))
+			.saturating_add(Weight::from_proof_size(8_256))
+			.saturating_add(Weight::from_proof_size(5_152).saturating_mul(c.into()))
+			.saturating_add(RocksDbWeight::get().reads(3))
+			.saturating_add(RocksDbWeight::get().reads((2_u64).saturating_mul(c.into())))
+			.saturating_add(RocksDbWeight::get().writes(1))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(c.into())))
+	}
+	// Storage: RngDao Cycles (r:1 w:1)
+	// Storage: RngDao BountyAssets (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: RngDao BountyContributions (r:1 w:1)
+	fn top_up_bounty() -> Weight {
+		Weight::from_ref_time(50_000_000)
+			.saturating_add(Weight::from_proof_size(11_164))
+			.saturating_add(RocksDbWeight::get().reads(5))
+			.saturating_add(RocksDbWeight::get().writes(4))
+	}
+	// Storage: RngDao PalletParameters (r:0 w:1)
+	fn set_parameters() -> Weight {
+		Weight::from_ref_time(20_000_000).saturating_add(RocksDbWeight::get().writes(1))
 	}
-	fn reveal_secret() -> Weight {
-		Weight::from_ref_time(10_000)
+	// Storage: RngDao Cycles (r:1 w:1)
+	// Storage: RngDao Generators (r:50 w:50)
+	// Storage: RngDao Reveals (r:50 w:50)
+	// Storage: RngDao BountyAssets (r:0 w:1)
+	// Storage: RngDao BountyContributions (r:0 w:1)
+	fn reap_cycle(u: u32, r: u32) -> Weight {
+		Weight::from_ref_time(30_000_000)
+			.saturating_add(Weight::from_ref_time(1_500_000).saturating_mul(u.into()))
+			.saturating_add(Weight::from_ref_time(1_500_000).saturating_mul(r.into()))
+			.saturating_add(Weight::from_proof_size(2_629))
+			.saturating_add(Weight::from_proof_size(2_589).saturating_mul(u.into()))
+			.saturating_add(Weight::from_proof_size(2_572).saturating_mul(r.into()))
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(u.into())))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(r.into())))
+			.saturating_add(RocksDbWeight::get().writes(3))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(u.into())))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(r.into())))
 	}
-	fn get_random_number() -> Weight {
-		Weight::from_ref_time(10_000)
+	// Storage: RngDao QueuedCallbacks (r:1 w:1)
+	// Storage: RngDao PendingRequests (r:1 w:1)
+	// Storage: RngDao ConsumerRequests (r:64 w:64)
+	fn deliver_randomness(n: u32) -> Weight {
+		Weight::from_ref_time(10_000_000)
+			.saturating_add(Weight::from_ref_time(5_000_000).saturating_mul(n.into()))
+			.saturating_add(Weight::from_proof_size(4_068))
+			.saturating_add(Weight::from_proof_size(2_503).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(2))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(n.into())))
 	}
 }
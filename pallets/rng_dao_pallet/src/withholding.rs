@@ -0,0 +1,37 @@
+//! Mitigations of withholding attacks.
+//!
+//! Secrets are revealed one by one and are public once revealed, so the last generator to reveal
+//! knows the random number of the cycle both if it reveals and if it does not. It can pick the one
+//! it likes by withholding its secret, which only costs it its deposit. The runtime's
+//! [`Withholding`] policy decides how expensive that choice is.
+
+use core::marker::PhantomData;
+use frame_support::traits::Get;
+use sp_runtime::{traits::AtLeast32BitUnsigned, Perbill};
+
+pub trait Withholding<Balance> {
+	/// Deposit generators of a cycle with `bounty` pay, `deposit` being the one of the cycle's
+	/// parameters.
+	fn deposit(deposit: Balance, bounty: &Balance) -> Balance;
+}
+
+/// No mitigation, generators pay the deposit of the cycle's parameters.
+impl<Balance> Withholding<Balance> for () {
+	fn deposit(deposit: Balance, _bounty: &Balance) -> Balance {
+		deposit
+	}
+}
+
+/// Deposits of at least `Ratio` of the bounty. Choosing the random number then costs a withholder
+/// a part of what the creator pays for it, however low the deposit of the cycle's parameters is.
+/// The pallet caps the deposit at the one of `MaxCycleParameters`, so that large bounties do not
+/// price generators out.
+pub struct ScaledDeposit<Ratio>(PhantomData<Ratio>);
+
+impl<Balance: AtLeast32BitUnsigned + Clone, Ratio: Get<Perbill>> Withholding<Balance>
+	for ScaledDeposit<Ratio>
+{
+	fn deposit(deposit: Balance, bounty: &Balance) -> Balance {
+		deposit.max(Ratio::get().mul_ceil(bounty.clone()))
+	}
+}
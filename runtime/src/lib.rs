@@ -6,6 +6,7 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+use codec::Encode;
 use pallet_grandpa::{
 	fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
 };
@@ -15,10 +16,11 @@ use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	traits::{
-		AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, One, Verify,
+		AccountIdConversion, AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount,
+		NumberFor, One, Verify,
 	},
 	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, MultiSignature,
+	ApplyExtrinsicResult, MultiSignature, SaturatedConversion,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -29,7 +31,8 @@ use sp_version::RuntimeVersion;
 pub use frame_support::{
 	construct_runtime, parameter_types,
 	traits::{
-		ConstU128, ConstU32, ConstU64, ConstU8, KeyOwnerProofSystem, Randomness, StorageInfo,
+		AsEnsureOriginWithArg, ConstU128, ConstU32, ConstU64, ConstU8, KeyOwnerProofSystem,
+		Randomness, StorageInfo,
 	},
 	weights::{
 		constants::{
@@ -47,8 +50,6 @@ use pallet_transaction_payment::{ConstFeeMultiplier, CurrencyAdapter, Multiplier
 pub use sp_runtime::BuildStorage;
 pub use sp_runtime::{Perbill, Permill};
 
-mod weights;
-
 /// Import the template pallet.
 pub use pallet_rng_dao;
 
@@ -107,10 +108,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 	state_version: 1,
 };
 
@@ -259,6 +260,33 @@ impl pallet_balances::Config for Runtime {
 	type WeightInfo = pallet_balances::weights::SubstrateWeight<Runtime>;
 }
 
+parameter_types! {
+	pub const AssetDeposit: Balance = 1_000_000;
+	pub const AssetAccountDeposit: Balance = 1_000;
+	pub const MetadataDepositBase: Balance = 10_000;
+	pub const MetadataDepositPerByte: Balance = 100;
+	pub const ApprovalDeposit: Balance = EXISTENTIAL_DEPOSIT;
+}
+
+impl pallet_assets::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Balance = Balance;
+	type AssetId = u32;
+	type Currency = Balances;
+	type CreateOrigin = AsEnsureOriginWithArg<frame_system::EnsureSigned<AccountId>>;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type AssetDeposit = AssetDeposit;
+	type AssetAccountDeposit = AssetAccountDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = ConstU32<50>;
+	type Freezer = ();
+	type Extra = ();
+	type RemoveItemsLimit = ConstU32<1000>;
+	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
+}
+
 parameter_types! {
 	pub FeeMultiplier: Multiplier = Multiplier::one();
 }
@@ -285,6 +313,39 @@ parameter_types! {
 	pub DelayBeforeSecondPhase: u32 = 2_u32;
 	pub SecondPhaseDuration: u32 = 5_u32;
 	pub MaxGenerators: u8 = 3_u8;
+	pub MinRevealers: u8 = 2_u8;
+	pub CommitPhaseExtension: u32 = 2_u32;
+	pub MaxCommitPhaseExtensions: u8 = 3_u8;
+	// twice the share of a generator, so withholding costs more than it pays
+	pub WithholdingDepositRatio: Perbill = Perbill::from_percent(50);
+	pub TreasuryAccount: AccountId = PalletId(*b"py/trsry").into_account_truncating();
+	pub MaxCyclesPerBlock: u32 = 16_u32;
+	// a minute of retries before the cycle is left to its creator
+	pub MaxFinalisationRetries: u32 = MINUTES;
+	pub MaxHistory: u32 = 256_u32;
+	pub RetentionPeriod: BlockNumber = 7 * DAYS;
+	pub MaxPendingRequests: u32 = 64_u32;
+	pub MaxRequestsPerConsumer: u32 = 16_u32;
+	pub MaxCallbackWeight: Weight = Perbill::from_percent(10) * BlockWeights::get().max_block;
+	pub MaxContributors: u32 = 16_u32;
+	pub MinCycleParameters: pallet_rng_dao::CycleParameters<Balance, BlockNumber> =
+		pallet_rng_dao::CycleParameters {
+			delay_before_bots: 1,
+			delay_before_second_phase: 1,
+			second_phase_duration: 1,
+			max_generators: 1,
+			deposit: 100,
+			min_revealers: 1,
+		};
+	pub MaxCycleParameters: pallet_rng_dao::CycleParameters<Balance, BlockNumber> =
+		pallet_rng_dao::CycleParameters {
+			delay_before_bots: DAYS,
+			delay_before_second_phase: DAYS,
+			second_phase_duration: DAYS,
+			max_generators: 100,
+			deposit: 1_000_000,
+			min_revealers: 100,
+		};
 }
 
 /// Configure the pallet-template in pallets/template.
@@ -295,14 +356,92 @@ impl pallet_rng_dao::Config for Runtime {
 	type Deposit = Deposit;
 	type MinBounty = MinBounty;
 	type Balances = Balances;
+	// `funds::NamedReserves<Balances>` keeps funds reserved on users' accounts instead, but needs
+	// all running cycles to be finalised before switching and `MaxReserves` to be raised
+	type Funds = pallet_rng_dao::funds::CycleAccount;
+	type AssetId = u32;
+	type Assets = Assets;
+	type AssetOrigin = frame_system::EnsureRoot<AccountId>;
 	type PalletId = TestPalletId;
 	type DelayBeforeBots = DelayBeforeBots;
 	type DelayBeforeSecondPhase = DelayBeforeSecondPhase;
 	type SecondPhaseDuration = SecondPhaseDuration;
 	type MaxGenerators = MaxGenerators;
-	type WeightInfo = weights::rng_dao_pallet::RuntimeWeight<Runtime>;
+	type MinRevealers = MinRevealers;
+	type CommitPhaseExtension = CommitPhaseExtension;
+	type MaxCommitPhaseExtensions = MaxCommitPhaseExtensions;
+	type MinCycleParameters = MinCycleParameters;
+	type MaxCycleParameters = MaxCycleParameters;
+	type TreasuryAccount = TreasuryAccount;
+	type MaxCyclesPerBlock = MaxCyclesPerBlock;
+	type MaxFinalisationRetries = MaxFinalisationRetries;
+	type Combiner = pallet_rng_dao::combiner::KeccakMerkleRoot;
+	type MaxHistory = MaxHistory;
+	type RetentionPeriod = RetentionPeriod;
+	// pallets requesting randomness with `RngDao::request_randomness` are added here
+	type Consumers = ();
+	type MaxPendingRequests = MaxPendingRequests;
+	type MaxRequestsPerConsumer = MaxRequestsPerConsumer;
+	type MaxCallbackWeight = MaxCallbackWeight;
+	type MaxContributors = MaxContributors;
+	type Withholding = pallet_rng_dao::withholding::ScaledDeposit<WithholdingDepositRatio>;
+	type BotOrigin = frame_system::EnsureRoot<AccountId>;
+	type AdminOrigin = frame_system::EnsureRoot<AccountId>;
+	type AuthorityId = pallet_rng_dao::crypto::AuthId;
+	type WeightInfo = pallet_rng_dao::weights::SubstrateWeight<Runtime>;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Runtime
+where
+	RuntimeCall: From<LocalCall>,
+{
+	fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: RuntimeCall,
+		public: <Signature as Verify>::Signer,
+		account: AccountId,
+		nonce: Index,
+	) -> Option<(
+		RuntimeCall,
+		<UncheckedExtrinsic as sp_runtime::traits::Extrinsic>::SignaturePayload,
+	)> {
+		let period =
+			BlockHashCount::get().checked_next_power_of_two().map(|c| c / 2).unwrap_or(2) as u64;
+		let current_block = System::block_number().saturated_into::<u64>().saturating_sub(1);
+		let extra: SignedExtra = (
+			frame_system::CheckNonZeroSender::<Runtime>::new(),
+			frame_system::CheckSpecVersion::<Runtime>::new(),
+			frame_system::CheckTxVersion::<Runtime>::new(),
+			frame_system::CheckGenesis::<Runtime>::new(),
+			frame_system::CheckEra::<Runtime>::from(generic::Era::mortal(period, current_block)),
+			frame_system::CheckNonce::<Runtime>::from(nonce),
+			frame_system::CheckWeight::<Runtime>::new(),
+			pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+		);
+		let raw_payload = SignedPayload::new(call, extra).ok()?;
+		let signature = raw_payload.using_encoded(|payload| C::sign(payload, public))?;
+		let (call, extra, _) = raw_payload.deconstruct();
+		Some((call, (sp_runtime::MultiAddress::Id(account), signature, extra)))
+	}
+}
+
+impl frame_system::offchain::SigningTypes for Runtime {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
 }
 
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+	RuntimeCall: From<C>,
+{
+	type Extrinsic = UncheckedExtrinsic;
+	type OverarchingCall = RuntimeCall;
+}
+
+/// Randomness source for pallets needing on-chain randomness, derived from the latest completed
+/// RNG DAO cycle. Use it instead of `RandomnessCollectiveFlip`, e.g.
+/// `type Randomness = DaoRandomness;` in a pallet's `Config`.
+pub type DaoRandomness = RngDao;
+
 // Create the runtime by composing the FRAME pallets that were previously configured.
 construct_runtime!(
 	pub struct Runtime
@@ -321,6 +460,7 @@ construct_runtime!(
 		Sudo: pallet_sudo,
 		// Include the custom logic from the pallet-template in the runtime.
 		RngDao: pallet_rng_dao,
+		Assets: pallet_assets,
 	}
 );
 
@@ -347,6 +487,15 @@ pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<RuntimeCall, SignedExtra>;
+/// Storage migrations executed on runtime upgrade.
+pub type Migrations = (
+	pallet_rng_dao::migrations::v1::MigrateToV1<Runtime>,
+	pallet_rng_dao::migrations::v2::MigrateToV2<Runtime>,
+	pallet_rng_dao::migrations::v3::MigrateToV3<Runtime>,
+	pallet_rng_dao::migrations::v4::MigrateToV4<Runtime>,
+	pallet_rng_dao::migrations::v5::MigrateToV5<Runtime>,
+	pallet_rng_dao::migrations::v6::MigrateToV6<Runtime>,
+);
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -354,6 +503,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -366,6 +516,7 @@ mod benches {
 		[frame_benchmarking, BaselineBench::<Runtime>]
 		[frame_system, SystemBench::<Runtime>]
 		[pallet_balances, Balances]
+		[pallet_assets, Assets]
 		[pallet_timestamp, Timestamp]
 		[pallet_rng_dao, RngDao]
 	);
@@ -519,6 +670,35 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_rng_dao_runtime_api::RngDaoApi<Block, AccountId, Balance, BlockNumber, u128, u32>
+		for Runtime
+	{
+		fn cycle(
+			cycle_id: u128,
+		) -> Option<pallet_rng_dao_runtime_api::CycleDetails<AccountId, Balance, BlockNumber>> {
+			RngDao::cycle_details(cycle_id)
+		}
+		fn generators(
+			cycle_id: u128,
+		) -> Vec<pallet_rng_dao_runtime_api::GeneratorDetails<AccountId>> {
+			RngDao::cycle_generators(cycle_id)
+		}
+		fn open_commitments(who: AccountId) -> Vec<u128> {
+			RngDao::open_commitments(who)
+		}
+		fn predicted_payout(
+			cycle_id: u128,
+			who: AccountId,
+		) -> Option<pallet_rng_dao_runtime_api::PredictedPayout<u32, Balance>> {
+			RngDao::predicted_payout(cycle_id, who)
+		}
+		fn randomness_at(
+			block: BlockNumber,
+		) -> Option<pallet_rng_dao_runtime_api::RandomnessRecord<u128, BlockNumber>> {
+			RngDao::randomness_at(block)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (
@@ -1 +0,0 @@
-pub mod rng_dao_pallet;
@@ -0,0 +1,22 @@
+#!/usr/bin/env bash
+# Generates the weights of pallet_rng_dao from its benchmarks. Meant to be run on the reference
+# hardware of the chain, the result replaces pallets/rng_dao_pallet/src/weights.rs.
+set -e
+
+cd "$(dirname "$0")/.."
+
+echo "*** Building node with runtime benchmarks"
+cargo build --release --features runtime-benchmarks
+
+echo "*** Running benchmarks of pallet_rng_dao"
+./target/release/node-template benchmark pallet \
+	--chain dev \
+	--execution wasm \
+	--wasm-execution compiled \
+	--pallet pallet_rng_dao \
+	--extrinsic '*' \
+	--steps 50 \
+	--repeat 20 \
+	--header .maintain/HEADER \
+	--template .maintain/frame-weight-template.hbs \
+	--output pallets/rng_dao_pallet/src/weights.rs